@@ -0,0 +1,380 @@
+//! Generates typed CDP (Chrome DevTools Protocol) bindings from the vendored protocol JSON
+
+use std::collections::{ BTreeMap, HashSet };
+use std::fmt::Write;
+use std::path::PathBuf;
+use serde_json::Value;
+
+/// The generated CDP domains
+const DOMAINS: &[&str] = &[
+    "Browser", "DOM", "Emulation", "Fetch", "Input", "Network", "Page", "Runtime", "Storage", "Target",
+];
+
+/// The vendored protocol files
+const PROTOCOLS: &[&str] = &["protocol/browser_protocol.json", "protocol/js_protocol.json"];
+
+fn main() {
+    let mut domains = BTreeMap::new();
+    for path in PROTOCOLS {
+        println!("cargo:rerun-if-changed={path}");
+
+        let text = std::fs::read_to_string(path).expect("Failed to read the CDP protocol file");
+        let json: Value = serde_json::from_str(&text).expect("Failed to parse the CDP protocol file");
+
+        for domain in json["domains"].as_array().expect("No CDP domains found") {
+            let name = domain["domain"].as_str().unwrap_or_default().to_owned();
+            if DOMAINS.contains(&name.as_str()) {
+                domains.insert(name, domain.clone());
+            }
+        }
+    }
+
+    let mut out = String::new();
+    for (name, domain) in &domains {
+        Generator::new(name, domain).generate(&mut out);
+    }
+
+    let out_path = PathBuf::from(std::env::var("OUT_DIR").unwrap()).join("cdp.rs");
+    std::fs::write(out_path, out).expect("Failed to write the generated CDP bindings");
+}
+
+/// The domain bindings generator
+struct Generator<'a> {
+    domain: &'a str,
+    json: &'a Value,
+    /// The domain types that can't derive 'Default'
+    no_default: HashSet<String>,
+    /// The domain types declared as structs
+    structs: HashSet<String>,
+    /// The generated inline enums
+    enums: Vec<String>,
+}
+
+impl<'a> Generator<'a> {
+    fn new(domain: &'a str, json: &'a Value) -> Self {
+        let mut structs = HashSet::new();
+        let mut no_default = HashSet::new();
+
+        for ty in array(&json["types"]) {
+            let id = ty["id"].as_str().unwrap_or_default().to_owned();
+            if ty.get("enum").is_some() {
+                no_default.insert(id.clone());
+            } else if ty["type"] == "object" && ty.get("properties").is_some() {
+                structs.insert(id.clone());
+            }
+        }
+
+        let mut this = Self { domain, json, no_default, structs, enums: vec![] };
+
+        // structs with required enum (or non-default struct) fields can't derive 'Default':
+        loop {
+            let mut changed = false;
+            for ty in array(&json["types"]) {
+                let id = ty["id"].as_str().unwrap_or_default();
+                if this.structs.contains(id) && !this.no_default.contains(id) && !this.can_default(&ty["properties"]) {
+                    this.no_default.insert(id.to_owned());
+                    changed = true;
+                }
+            }
+            if !changed { break; }
+        }
+
+        this
+    }
+
+    /// Returns true if all required properties implement 'Default'
+    fn can_default(&self, properties: &Value) -> bool {
+        array(properties).iter().all(|prop| {
+            if prop["optional"] == true {
+                return true;
+            }
+            if prop.get("enum").is_some() {
+                return false;
+            }
+            match prop.get("$ref").and_then(|r| r.as_str()) {
+                Some(r) if !r.contains('.') => !self.no_default.contains(r),
+                Some(r) => {
+                    let (domain, _) = r.split_once('.').unwrap();
+                    !DOMAINS.contains(&domain)
+                },
+                None => true,
+            }
+        })
+    }
+
+    fn generate(mut self, out: &mut String) {
+        let mut body = String::new();
+
+        for ty in array(&self.json["types"]) {
+            self.generate_type(&ty, &mut body);
+        }
+        for command in array(&self.json["commands"]) {
+            self.generate_command(&command, &mut body);
+        }
+        for event in array(&self.json["events"]) {
+            self.generate_event(&event, &mut body);
+        }
+
+        let _ = writeln!(out, "{}", doc(&self.json["description"], ""));
+        let _ = writeln!(out, "#[allow(non_snake_case, clippy::all)]");
+        let _ = writeln!(out, "pub mod {} {{", self.domain);
+        let _ = writeln!(out, "    #[allow(unused_imports)]");
+        let _ = writeln!(out, "    use super::{{ Command, Event }};");
+        let _ = writeln!(out, "    use serde::{{ Serialize, Deserialize }};\n");
+        let _ = writeln!(out, "    /// The domain name");
+        let _ = writeln!(out, "    pub const DOMAIN: &str = {:?};\n", self.domain);
+        for e in std::mem::take(&mut self.enums) {
+            out.push_str(&e);
+        }
+        out.push_str(&body);
+        let _ = writeln!(out, "}}\n");
+    }
+
+    fn generate_type(&mut self, ty: &Value, out: &mut String) {
+        let id = ty["id"].as_str().unwrap_or_default();
+
+        if let Some(values) = ty.get("enum") {
+            self.enums.push(enumeration(id, &ty["description"], values));
+        } else if self.structs.contains(id) {
+            let can_default = !self.no_default.contains(id);
+            let fields = self.fields(id, &ty["properties"]);
+            out.push_str(&structure(id, &ty["description"], &fields, can_default));
+        } else {
+            let rust = self.rust_type(ty, id, "");
+            let _ = writeln!(out, "{}", doc(&ty["description"], "    "));
+            let _ = writeln!(out, "    pub type {id} = {rust};\n");
+        }
+    }
+
+    fn generate_command(&mut self, command: &Value, out: &mut String) {
+        let name = upper_first(command["name"].as_str().unwrap_or_default());
+        let method = fmt_method(self.domain, command["name"].as_str().unwrap_or_default());
+        let returns = format!("{name}Returns");
+
+        let params = self.fields(&name, &command["parameters"]);
+        let can_default = self.can_default(&command["parameters"]);
+        out.push_str(&structure(&name, &command["description"], &params, can_default));
+
+        let fields = self.fields(&returns, &command["returns"]);
+        let description = Value::String(format!("The result of '{method}'"));
+        out.push_str(&structure(&returns, &description, &fields, false));
+
+        let _ = writeln!(out, "    impl Command for {name} {{");
+        let _ = writeln!(out, "        const METHOD: &'static str = {method:?};");
+        let _ = writeln!(out, "        type Response = {returns};");
+        let _ = writeln!(out, "    }}\n");
+    }
+
+    fn generate_event(&mut self, event: &Value, out: &mut String) {
+        let name = upper_first(event["name"].as_str().unwrap_or_default());
+        let method = fmt_method(self.domain, event["name"].as_str().unwrap_or_default());
+
+        let fields = self.fields(&name, &event["parameters"]);
+        out.push_str(&structure(&name, &event["description"], &fields, false));
+
+        let _ = writeln!(out, "    impl Event for {name} {{");
+        let _ = writeln!(out, "        const METHOD: &'static str = {method:?};");
+        let _ = writeln!(out, "    }}\n");
+    }
+
+    /// Generates the struct fields as (json name, rust name, rust type, optional, description)
+    fn fields(&mut self, owner: &str, properties: &Value) -> Vec<Field> {
+        array(properties).iter().map(|prop| {
+            let name = prop["name"].as_str().unwrap_or_default();
+            let mut rust = self.rust_type(prop, owner, name);
+
+            // recursive types must be boxed:
+            if rust == owner {
+                rust = format!("Box<{rust}>");
+            }
+
+            Field {
+                json: name.to_owned(),
+                rust: snake_case(name),
+                ty: rust,
+                optional: prop["optional"] == true,
+                description: prop["description"].clone(),
+            }
+        }).collect()
+    }
+
+    /// Returns the rust type of the property
+    fn rust_type(&mut self, prop: &Value, owner: &str, name: &str) -> String {
+        if let Some(r) = prop.get("$ref").and_then(|r| r.as_str()) {
+            return match r.split_once('.') {
+                Some((domain, id)) if DOMAINS.contains(&domain) => format!("super::{domain}::{id}"),
+                Some(_) => "serde_json::Value".to_owned(),
+                None => r.to_owned(),
+            };
+        }
+
+        match prop["type"].as_str().unwrap_or_default() {
+            "string" if prop.get("enum").is_some() && !name.is_empty() => {
+                let id = format!("{owner}{}", upper_first(name));
+                self.enums.push(enumeration(&id, &prop["description"], &prop["enum"]));
+                id
+            },
+            "string" => "String".to_owned(),
+            "integer" => "i64".to_owned(),
+            "number" => "f64".to_owned(),
+            "boolean" => "bool".to_owned(),
+            "array" => format!("Vec<{}>", self.rust_type(&prop["items"], owner, name)),
+            _ => "serde_json::Value".to_owned(),
+        }
+    }
+}
+
+/// The struct field description
+struct Field {
+    json: String,
+    rust: String,
+    ty: String,
+    optional: bool,
+    description: Value,
+}
+
+/// Generates a struct with serde attributes
+fn structure(name: &str, description: &Value, fields: &[Field], can_default: bool) -> String {
+    let mut out = String::new();
+    let derive = if can_default { "Debug, Clone, Default, PartialEq, Serialize, Deserialize" } else { "Debug, Clone, PartialEq, Serialize, Deserialize" };
+
+    let _ = writeln!(out, "{}", doc(description, "    "));
+    let _ = writeln!(out, "    #[derive({derive})]");
+    let _ = writeln!(out, "    pub struct {name} {{");
+    for field in fields {
+        let _ = writeln!(out, "{}", doc(&field.description, "        "));
+        let _ = writeln!(out, "        #[serde(rename = {:?}{})]", field.json, if field.optional { ", default, skip_serializing_if = \"Option::is_none\"" } else { "" });
+        if field.optional {
+            let _ = writeln!(out, "        pub {}: Option<{}>,", field.rust, field.ty);
+        } else {
+            let _ = writeln!(out, "        pub {}: {},", field.rust, field.ty);
+        }
+    }
+    let _ = writeln!(out, "    }}\n");
+
+    out
+}
+
+/// Generates a string enumeration
+fn enumeration(name: &str, description: &Value, values: &Value) -> String {
+    let mut out = String::new();
+    let mut variants = HashSet::new();
+
+    let _ = writeln!(out, "{}", doc(description, "    "));
+    let _ = writeln!(out, "    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]");
+    let _ = writeln!(out, "    pub enum {name} {{");
+    for value in array(values) {
+        let value = value.as_str().unwrap_or_default();
+        let mut variant = pascal_case(value);
+        while !variants.insert(variant.clone()) {
+            variant.push('_');
+        }
+
+        let _ = writeln!(out, "        #[serde(rename = {value:?})]");
+        let _ = writeln!(out, "        {variant},");
+    }
+    let fallback = if variants.contains("Unknown") { "Unrecognized" } else { "Unknown" };
+    let _ = writeln!(out, "        /// The value is unknown for this protocol version (received only, it can't be sent)");
+    let _ = writeln!(out, "        #[serde(other, skip_serializing)]");
+    let _ = writeln!(out, "        {fallback},");
+    let _ = writeln!(out, "    }}\n");
+
+    out
+}
+
+/// Generates a doc comment (indented code blocks are flattened to avoid doc tests)
+fn doc(description: &Value, indent: &str) -> String {
+    let text = escape(&description.as_str().unwrap_or_default().replace("```", ""));
+    let lines = text.lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .map(|line| format!("{indent}/// {line}"))
+        .collect::<Vec<_>>();
+
+    if lines.is_empty() { format!("{indent}///") } else { lines.join("\n") }
+}
+
+/// Escapes the markdown of the protocol description for rustdoc (the brackets aren't links, the URLs are wrapped in '<...>')
+fn escape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut code = false;
+    let mut rest = text;
+
+    while let Some(c) = rest.chars().next() {
+        // the URLs outside the code spans are wrapped:
+        if !code && (rest.starts_with("http://") || rest.starts_with("https://")) {
+            let end = rest.find(|c: char| c.is_whitespace() || "()<>\"'`".contains(c)).unwrap_or(rest.len());
+            let url = rest[..end].trim_end_matches(['.', ',', ';', ':']);
+            result.push_str(&format!("<{url}>"));
+            rest = &rest[url.len()..];
+            continue;
+        }
+
+        match c {
+            '`' => code = !code,
+            '[' | ']' if !code => result.push('\\'),
+            _ => {}
+        }
+        result.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+
+    result
+}
+
+fn fmt_method(domain: &str, name: &str) -> String {
+    format!("{domain}.{name}")
+}
+
+fn array(value: &Value) -> Vec<Value> {
+    value.as_array().cloned().unwrap_or_default()
+}
+
+fn upper_first(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(c) => c.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Converts the JSON name (camelCase, kebab-case, etc) to PascalCase
+fn pascal_case(s: &str) -> String {
+    let mut out = String::new();
+    for part in s.split(|c: char| !c.is_ascii_alphanumeric()).filter(|p| !p.is_empty()) {
+        out.push_str(&upper_first(part));
+    }
+
+    if out.is_empty() || out.starts_with(|c: char| c.is_ascii_digit()) {
+        out.insert(0, 'V');
+    }
+    out
+}
+
+/// Converts the JSON name (camelCase) to snake_case
+fn snake_case(s: &str) -> String {
+    let chars = s.chars().collect::<Vec<_>>();
+    let mut out = String::new();
+
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_ascii_uppercase() {
+            // split on 'aB' and on the last capital of an abbreviation ('URLFor' -> 'url_for'):
+            let prev_lower = i > 0 && (chars[i - 1].is_ascii_lowercase() || chars[i - 1].is_ascii_digit());
+            let next_lower = i > 0 && chars.get(i + 1).is_some_and(|n| n.is_ascii_lowercase()) && chars[i - 1].is_ascii_uppercase();
+            if prev_lower || next_lower {
+                out.push('_');
+            }
+            out.push(c.to_ascii_lowercase());
+        } else if c == '-' {
+            out.push('_');
+        } else {
+            out.push(c);
+        }
+    }
+
+    match out.as_str() {
+        "self" | "super" | "crate" => format!("{out}_"),
+        "type" | "override" | "ref" | "static" | "struct" | "enum" | "match" | "mod" | "move" | "loop" | "fn" | "impl" | "in" | "use" | "where" | "async" | "await" | "dyn" | "box" | "final" | "yield" | "abstract" | "virtual" | "macro" | "priv" | "gen" => format!("r#{out}"),
+        _ => out,
+    }
+}
@@ -0,0 +1,267 @@
+use serde::{ Serialize, Deserialize };
+use serde_json::Value;
+
+/// The WebDriver BiDi event
+#[derive(Debug, Clone, PartialEq)]
+pub enum BiDiEvent {
+    /// 'log.entryAdded'
+    LogEntryAdded(LogEntry),
+    /// 'network.beforeRequestSent'
+    BeforeRequestSent(BeforeRequestSent),
+    /// 'browsingContext.contextCreated'
+    ContextCreated(ContextInfo),
+    /// 'browsingContext.contextDestroyed'
+    ContextDestroyed(ContextInfo),
+    /// 'browsingContext.navigationStarted'
+    NavigationStarted(NavigationInfo),
+    /// 'browsingContext.fragmentNavigated'
+    FragmentNavigated(NavigationInfo),
+    /// 'browsingContext.domContentLoaded'
+    DomContentLoaded(NavigationInfo),
+    /// 'browsingContext.load'
+    Load(NavigationInfo),
+    /// 'browsingContext.downloadWillBegin'
+    DownloadWillBegin(NavigationInfo),
+    /// 'browsingContext.navigationAborted'
+    NavigationAborted(NavigationInfo),
+    /// 'browsingContext.navigationFailed'
+    NavigationFailed(NavigationInfo),
+    /// 'browsingContext.userPromptOpened'
+    UserPromptOpened(UserPromptOpened),
+    /// 'browsingContext.userPromptClosed'
+    UserPromptClosed(UserPromptClosed),
+    /// Any other event (or the event with unexpected parameters)
+    Other { method: String, params: Value },
+    /// The events lost by a slow subscriber (see 'cdp::EVENTS_CAPACITY')
+    Lagged { missed: u64 },
+}
+
+impl BiDiEvent {
+    /// Parses the event by its method name
+    pub(crate) fn parse(method: &str, params: Value) -> Self {
+        fn parse<T: serde::de::DeserializeOwned>(params: &Value) -> Option<T> {
+            serde_json::from_value(params.clone()).ok()
+        }
+
+        let event = match method {
+            "log.entryAdded" => parse(&params).map(Self::LogEntryAdded),
+            "network.beforeRequestSent" => parse(&params).map(Self::BeforeRequestSent),
+            "browsingContext.contextCreated" => parse(&params).map(Self::ContextCreated),
+            "browsingContext.contextDestroyed" => parse(&params).map(Self::ContextDestroyed),
+            "browsingContext.navigationStarted" => parse(&params).map(Self::NavigationStarted),
+            "browsingContext.fragmentNavigated" => parse(&params).map(Self::FragmentNavigated),
+            "browsingContext.domContentLoaded" => parse(&params).map(Self::DomContentLoaded),
+            "browsingContext.load" => parse(&params).map(Self::Load),
+            "browsingContext.downloadWillBegin" => parse(&params).map(Self::DownloadWillBegin),
+            "browsingContext.navigationAborted" => parse(&params).map(Self::NavigationAborted),
+            "browsingContext.navigationFailed" => parse(&params).map(Self::NavigationFailed),
+            "browsingContext.userPromptOpened" => parse(&params).map(Self::UserPromptOpened),
+            "browsingContext.userPromptClosed" => parse(&params).map(Self::UserPromptClosed),
+            _ => None,
+        };
+
+        event.unwrap_or_else(|| Self::Other { method: method.to_owned(), params })
+    }
+
+    /// Returns the browsing context id of the event (if any)
+    pub fn context(&self) -> Option<&str> {
+        match self {
+            Self::LogEntryAdded(e) => e.source.context.as_deref(),
+            Self::BeforeRequestSent(e) => e.context.as_deref(),
+            Self::ContextCreated(e) | Self::ContextDestroyed(e) => Some(&e.context),
+            Self::NavigationStarted(e) | Self::FragmentNavigated(e) | Self::DomContentLoaded(e) | Self::Load(e)
+            | Self::DownloadWillBegin(e) | Self::NavigationAborted(e) | Self::NavigationFailed(e) => Some(&e.context),
+            Self::UserPromptOpened(e) => Some(&e.context),
+            Self::UserPromptClosed(e) => Some(&e.context),
+            Self::Other { params, .. } => params["context"].as_str(),
+            Self::Lagged { .. } => None,
+        }
+    }
+}
+
+/// The log entry source
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogSource {
+    pub realm: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+}
+
+/// The 'log.entryAdded' event (console message or JavaScript error)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogEntry {
+    /// The entry type ('console' or 'javascript')
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// The level ('debug', 'info', 'warn' or 'error')
+    pub level: String,
+    pub source: LogSource,
+    pub text: Option<String>,
+    /// The time in milliseconds since the UNIX epoch
+    pub timestamp: u64,
+    /// The console method (e.g. 'log', 'warn')
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    /// The console call arguments (remote values)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub args: Option<Vec<Value>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stack_trace: Option<Value>,
+}
+
+/// The HTTP header
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Header {
+    pub name: String,
+    /// The header value ({ "type": "string", "value": "..." } or base64 bytes)
+    pub value: Value,
+}
+
+impl Header {
+    /// Returns the header value as a string
+    pub fn value_str(&self) -> Option<&str> {
+        self.value["value"].as_str()
+    }
+}
+
+/// The network request data
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestData {
+    pub request: String,
+    pub url: String,
+    pub method: String,
+    pub headers: Vec<Header>,
+    #[serde(default)]
+    pub cookies: Vec<Value>,
+    #[serde(default)]
+    pub headers_size: u64,
+    #[serde(default)]
+    pub body_size: Option<u64>,
+    #[serde(default)]
+    pub timings: Value,
+}
+
+/// The 'network.beforeRequestSent' event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BeforeRequestSent {
+    pub context: Option<String>,
+    pub is_blocked: bool,
+    pub navigation: Option<String>,
+    pub redirect_count: u64,
+    pub request: RequestData,
+    pub timestamp: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub initiator: Option<Value>,
+}
+
+/// The browsing context info ('browsingContext.contextCreated' & 'contextDestroyed')
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextInfo {
+    pub context: String,
+    pub url: String,
+    #[serde(default)]
+    pub children: Option<Vec<ContextInfo>>,
+    #[serde(default)]
+    pub parent: Option<String>,
+    #[serde(default)]
+    pub user_context: Option<String>,
+    #[serde(default)]
+    pub original_opener: Option<String>,
+}
+
+/// The navigation info ('browsingContext.load', 'navigationStarted', etc)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NavigationInfo {
+    pub context: String,
+    pub navigation: Option<String>,
+    pub timestamp: u64,
+    pub url: String,
+}
+
+/// The 'browsingContext.userPromptOpened' event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserPromptOpened {
+    pub context: String,
+    /// The prompt type ('alert', 'beforeunload', 'confirm' or 'prompt')
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub message: String,
+    #[serde(default)]
+    pub default_value: Option<String>,
+}
+
+/// The 'browsingContext.userPromptClosed' event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserPromptClosed {
+    pub context: String,
+    pub accepted: bool,
+    #[serde(default)]
+    pub user_text: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parses_log_entry() {
+        let event = BiDiEvent::parse("log.entryAdded", json!({
+            "type": "console",
+            "level": "warn",
+            "source": { "realm": "r1", "context": "ctx1" },
+            "text": "hello",
+            "timestamp": 1700000000000u64,
+            "method": "warn",
+            "args": [{ "type": "string", "value": "hello" }],
+        }));
+
+        let BiDiEvent::LogEntryAdded(entry) = &event else { panic!("unexpected event: {event:?}") };
+        assert_eq!((entry.kind.as_str(), entry.level.as_str()), ("console", "warn"));
+        assert_eq!(entry.text.as_deref(), Some("hello"));
+        assert_eq!(entry.method.as_deref(), Some("warn"));
+        assert_eq!(event.context(), Some("ctx1"));
+    }
+
+    #[test]
+    fn parses_request_and_navigation_events() {
+        let event = BiDiEvent::parse("network.beforeRequestSent", json!({
+            "context": "ctx1",
+            "isBlocked": false,
+            "navigation": null,
+            "redirectCount": 0,
+            "request": {
+                "request": "req1",
+                "url": "https://example.com/",
+                "method": "GET",
+                "headers": [{ "name": "Accept", "value": { "type": "string", "value": "*/*" } }],
+            },
+            "timestamp": 1,
+        }));
+
+        let BiDiEvent::BeforeRequestSent(sent) = &event else { panic!("unexpected event: {event:?}") };
+        assert_eq!(sent.request.url, "https://example.com/");
+        assert_eq!(sent.request.headers[0].value_str(), Some("*/*"));
+
+        let event = BiDiEvent::parse("browsingContext.load", json!({ "context": "ctx2", "navigation": "nav1", "timestamp": 2, "url": "https://example.com/" }));
+        assert!(matches!(&event, BiDiEvent::Load(info) if info.navigation.as_deref() == Some("nav1")));
+        assert_eq!(event.context(), Some("ctx2"));
+    }
+
+    #[test]
+    fn keeps_unknown_and_malformed_events() {
+        let event = BiDiEvent::parse("script.realmCreated", json!({ "realm": "r1", "context": "ctx1" }));
+        assert!(matches!(&event, BiDiEvent::Other { method, .. } if method == "script.realmCreated"));
+        assert_eq!(event.context(), Some("ctx1"));
+
+        // the known event with unexpected parameters:
+        let event = BiDiEvent::parse("browsingContext.load", json!({ "context": "ctx1" }));
+        assert!(matches!(&event, BiDiEvent::Other { method, params } if method == "browsingContext.load" && params["context"] == "ctx1"));
+    }
+}
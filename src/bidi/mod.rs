@@ -0,0 +1,99 @@
+use crate::prelude::*;
+use crate::cdp::EventStream;
+use crate::ws::WsClient;
+
+pub mod events;  pub use events::*;
+
+use futures::stream::{ self, StreamExt };
+use serde_json::{ json, Value };
+use tokio::sync::broadcast::error::RecvError;
+
+/// The WebDriver BiDi client (the standard event channel, works with remote drivers too)
+#[derive(Clone)]
+pub struct BiDi {
+    ws: Arc<WsClient>,
+}
+
+impl BiDi {
+    /// Connects to the BiDi WebSocket URL (the 'webSocketUrl' session capability)
+    pub async fn connect(url: &str) -> Result<Self> {
+        Ok(Self {
+            ws: Arc::new(WsClient::connect(url).await?),
+        })
+    }
+
+    /// Returns true if the WebSocket connection is closed (the commands fail and the events streams are ended)
+    pub fn is_closed(&self) -> bool {
+        self.ws.is_closed()
+    }
+
+    /// Sends the BiDi command and returns the result
+    pub async fn send(&self, method: &str, params: Value) -> Result<Value> {
+        let response = self.ws.call(json!({ "method": method, "params": params })).await?;
+
+        match response["type"].as_str() {
+            Some("success") => Ok(response.get("result").cloned().unwrap_or_else(|| json!({}))),
+            _ => Err(Error::BiDiCommandFailed(response).into()),
+        }
+    }
+
+    /// Subscribes to the events (e.g. 'log.entryAdded', 'browsingContext'),
+    /// * contexts: limit events to the browsing contexts (None = all contexts)
+    /// * returns: the subscription id (if supported by the driver)
+    pub async fn subscribe(&self, events: &[&str], contexts: Option<&[&str]>) -> Result<Option<String>> {
+        let mut params = json!({ "events": events });
+        if let Some(contexts) = contexts {
+            params["contexts"] = json!(contexts);
+        }
+
+        let result = self.send("session.subscribe", params).await?;
+        Ok(result["subscription"].as_str().map(|s| s.to_owned()))
+    }
+
+    /// Removes the subscription by its id
+    pub async fn unsubscribe(&self, subscription: &str) -> Result<()> {
+        self.send("session.unsubscribe", json!({ "subscriptions": [subscription] })).await?;
+        Ok(())
+    }
+
+    /// Returns the stream of all subscribed events,
+    /// note: a slower subscriber loses the oldest events and gets the 'BiDiEvent::Lagged' event instead (see 'CdpSession::raw_events')
+    pub fn events(&self) -> EventStream<BiDiEvent> {
+        stream::unfold(self.ws.subscribe(), |mut receiver| async move {
+            loop {
+                let message = match receiver.recv().await {
+                    Ok(message) => message,
+                    Err(RecvError::Lagged(missed)) => return Some((BiDiEvent::Lagged { missed }, receiver)),
+                    Err(RecvError::Closed) => return None,
+                };
+
+                if message["type"] != "event" { continue; }
+                let Some(method) = message["method"].as_str() else { continue };
+
+                let event = BiDiEvent::parse(method, message.get("params").cloned().unwrap_or(Value::Null));
+                return Some((event, receiver));
+            }
+        }).boxed()
+    }
+
+    /// Returns the browsing contexts tree (tabs & frames)
+    pub async fn get_tree(&self) -> Result<Vec<ContextInfo>> {
+        let result = self.send("browsingContext.getTree", json!({})).await?;
+        Ok(serde_json::from_value(result["contexts"].clone())?)
+    }
+
+    /// Evaluates the JavaScript expression in the browsing context (the tab id) and returns the remote value,
+    /// a thrown exception is returned as an error
+    pub async fn evaluate(&self, context: &str, expression: &str, await_promise: bool) -> Result<Value> {
+        let result = self.send("script.evaluate", json!({
+            "expression": expression,
+            "target": { "context": context },
+            "awaitPromise": await_promise,
+        })).await?;
+
+        match result["type"].as_str() {
+            Some("success") => Ok(result["result"].clone()),
+            _ => Err(Error::BiDiCommandFailed(result["exceptionDetails"].clone()).into()),
+        }
+    }
+}
@@ -0,0 +1,224 @@
+use crate::prelude::*;
+use crate::ws::{ self, WsClient };
+use super::{ Command, Event };
+
+use std::collections::{ HashMap, HashSet };
+use futures::stream::{ self, BoxStream, StreamExt };
+use reqwest::Client;
+use serde_json::{ json, Value };
+use tokio::sync::broadcast::{ self, error::RecvError };
+
+/// The stream of CDP events
+pub type EventStream<T> = BoxStream<'static, T>;
+
+/// The number of events buffered for the subscribers (a slower subscriber loses the oldest ones)
+pub const EVENTS_CAPACITY: usize = ws::EVENTS_CAPACITY;
+
+/// The domains enabled automatically on the first events subscription
+const AUTO_ENABLE: &[&str] = &["DOM", "Log", "Network", "Page", "Runtime"];
+
+/// The connection to the browser DevTools WebSocket
+pub(crate) struct CdpConnection {
+    ws: WsClient,
+    /// The attached targets (target id -> session id)
+    targets: Mutex<HashMap<String, String>>,
+    /// The enabled domains (session id, domain)
+    enabled: Mutex<HashSet<(Option<String>, String)>>,
+}
+
+impl CdpConnection {
+    /// Connects to the browser by its debugger address (e.g. '127.0.0.1:9222')
+    pub(crate) async fn connect(client: &Client, debugger_address: &str) -> Result<Self> {
+        let version = client
+            .get(fmt!("http://{debugger_address}/json/version"))
+            .send()
+            .await
+            .map_err(|e| Error::CdpConnectionFailed(e.to_string()))?
+            .json::<Value>()
+            .await
+            .map_err(|e| Error::CdpConnectionFailed(e.to_string()))?;
+
+        let url = version["webSocketDebuggerUrl"]
+            .as_str()
+            .ok_or_else(|| Error::CdpConnectionFailed(str!("no 'webSocketDebuggerUrl' in '/json/version'")))?;
+
+        Ok(Self {
+            ws: WsClient::connect(url).await.map_err(|e| Error::CdpConnectionFailed(e.to_string()))?,
+            targets: Mutex::new(HashMap::new()),
+            enabled: Mutex::new(HashSet::new()),
+        })
+    }
+
+    /// Sends the command to the browser (session_id = None) or to the attached target
+    pub(crate) async fn send(&self, session_id: Option<&str>, method: &str, params: Value) -> Result<Value> {
+        let mut message = json!({ "method": method, "params": params });
+        if let Some(session_id) = session_id {
+            message["sessionId"] = session_id.into();
+        }
+
+        let response = self.ws.call(message).await?;
+        if let Some(error) = response.get("error") {
+            return Err(Error::CdpCommandFailed(error.clone()).into());
+        }
+
+        Ok(response.get("result").cloned().unwrap_or_else(|| json!({})))
+    }
+
+    /// Attaches to the target and returns the session id (cached per target)
+    pub(crate) async fn attach(&self, target_id: &str) -> Result<String> {
+        let mut targets = self.targets.lock().await;
+        if let Some(session_id) = targets.get(target_id) {
+            return Ok(session_id.clone());
+        }
+
+        let result = self.send(None, "Target.attachToTarget", json!({ "targetId": target_id, "flatten": true })).await?;
+        let session_id = result["sessionId"]
+            .as_str()
+            .ok_or(Error::UnexpectedResponse)?
+            .to_owned();
+
+        targets.insert(target_id.to_owned(), session_id.clone());
+        Ok(session_id)
+    }
+
+    /// Forgets the attached target session
+    pub(crate) async fn detach(&self, target_id: &str) {
+        if let Some(session_id) = self.targets.lock().await.remove(target_id) {
+            self.enabled.lock().await.retain(|(s, _)| s.as_deref() != Some(session_id.as_str()));
+        }
+    }
+
+    /// Returns true if the WebSocket connection is closed (the browser is disconnected)
+    pub(crate) fn is_closed(&self) -> bool {
+        self.ws.is_closed()
+    }
+
+    /// Subscribes to all incoming events
+    pub(crate) fn subscribe(&self) -> broadcast::Receiver<Value> {
+        self.ws.subscribe()
+    }
+}
+
+/// The raw CDP event
+#[derive(Debug, Clone, PartialEq)]
+pub struct RawEvent {
+    /// The protocol method name (e.g. 'Network.requestWillBeSent')
+    pub method: String,
+    /// The event parameters
+    pub params: Value,
+}
+
+impl RawEvent {
+    /// The method of the event sent to a slow subscriber instead of the lost events (params: '{"missed": count}')
+    pub const LAGGED: &'static str = "CdpSession.lagged";
+
+    /// Returns the number of the lost events if the subscriber lagged behind
+    pub fn lagged(&self) -> Option<u64> {
+        (self.method == Self::LAGGED).then(|| self.params["missed"].as_u64().unwrap_or_default())
+    }
+}
+
+/// The CDP session over the DevTools WebSocket (browser-level or attached to a tab)
+#[derive(Clone)]
+pub struct CdpSession {
+    connection: Arc<CdpConnection>,
+    session_id: Option<String>,
+}
+
+impl CdpSession {
+    /// Creates a new session handle
+    pub(crate) fn new(connection: Arc<CdpConnection>, session_id: Option<String>) -> Self {
+        Self { connection, session_id }
+    }
+
+    /// Returns the target session id (None = browser-level session)
+    pub fn session_id(&self) -> Option<&str> {
+        self.session_id.as_deref()
+    }
+
+    /// Sends the CDP command and returns the result
+    pub async fn send(&self, method: &str, params: Value) -> Result<Value> {
+        self.connection.send(self.session_id(), method, params).await
+    }
+
+    /// Executes the typed CDP command
+    pub async fn execute<C: Command>(&self, command: C) -> Result<C::Response> {
+        let result = self.send(C::METHOD, super::params(&command)?).await?;
+        super::response::<C>(result)
+    }
+
+    /// Enables the domain events (e.g. 'Network'), does nothing if it's already enabled
+    pub async fn enable(&self, domain: &str) -> Result<()> {
+        let key = (self.session_id.clone(), domain.to_owned());
+        if self.connection.enabled.lock().await.contains(&key) {
+            return Ok(());
+        }
+
+        self.send(&fmt!("{domain}.enable"), json!({})).await?;
+        self.connection.enabled.lock().await.insert(key);
+
+        Ok(())
+    }
+
+    /// Returns the stream of all events of this session,
+    /// note: the connection buffers the last 'EVENTS_CAPACITY' (4096) events, a slower subscriber loses the oldest ones
+    /// and gets the 'RawEvent::LAGGED' event instead
+    pub fn raw_events(&self) -> EventStream<RawEvent> {
+        let session_id = self.session_id.clone();
+
+        stream::unfold(self.connection.subscribe(), move |mut receiver| {
+            let session_id = session_id.clone();
+            async move {
+                loop {
+                    let message = match receiver.recv().await {
+                        Ok(message) => message,
+                        Err(RecvError::Lagged(missed)) => {
+                            let event = RawEvent { method: str!(RawEvent::LAGGED), params: json!({ "missed": missed }) };
+                            return Some((event, receiver));
+                        },
+                        Err(RecvError::Closed) => return None,
+                    };
+
+                    if message.get("sessionId").and_then(|s| s.as_str()) != session_id.as_deref() {
+                        continue;
+                    }
+                    let Some(method) = message.get("method").and_then(|m| m.as_str()) else { continue };
+
+                    let event = RawEvent {
+                        method: method.to_owned(),
+                        params: message.get("params").cloned().unwrap_or(Value::Null),
+                    };
+                    return Some((event, receiver));
+                }
+            }
+        }).boxed()
+    }
+
+    /// Returns the stream of all events of this session with the domains enabled (e.g. ["Network", "Page"]),
+    /// note: the stream is subscribed before enabling, so the first events sent on enabling are not missed
+    pub async fn subscribe(&self, domains: &[&str]) -> Result<EventStream<RawEvent>> {
+        let events = self.raw_events();
+        for domain in domains {
+            self.enable(domain).await?;
+        }
+
+        Ok(events)
+    }
+
+    /// Returns the stream of typed events (e.g. 'cdp::Network::ResponseReceived'),
+    /// common domains (Network, Page, Runtime, DOM, Log) are enabled automatically,
+    /// note: the events lost by a slow subscriber are skipped (see 'CdpSession::raw_events')
+    pub async fn events<E: Event + Send + 'static>(&self) -> Result<EventStream<E>> {
+        let domains = match E::METHOD.split_once('.') {
+            Some((domain, _)) if AUTO_ENABLE.contains(&domain) => vec![domain],
+            _ => vec![],
+        };
+
+        Ok(self.subscribe(&domains).await?
+            .filter_map(|event| async move {
+                if event.method != E::METHOD { return None; }
+                serde_json::from_value::<E>(event.params).ok()
+            })
+            .boxed())
+    }
+}
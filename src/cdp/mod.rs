@@ -0,0 +1,94 @@
+use crate::prelude::*;
+
+pub mod connection;  pub use connection::{ CdpSession, EventStream, RawEvent, EVENTS_CAPACITY };
+pub(crate) use connection::CdpConnection;
+
+use reqwest::Client;
+use serde::{ Serialize, de::DeserializeOwned };
+use serde_json::{ json, Value };
+
+/// Sends the CDP command through the chromedriver HTTP bridge
+/// * session_url: the WebDriver session URL
+/// * with_result: wait for the command result ('/goog/cdp/execute') or just send it ('/chromium/send_command')
+pub(crate) async fn send(client: &Client, session_url: &str, method: &str, params: Value, with_result: bool) -> Result<Value> {
+    let url = if with_result {
+        fmt!("{session_url}/goog/cdp/execute")
+    } else {
+        fmt!("{session_url}/chromium/send_command")
+    };
+
+    let response = client
+        .post(&url)
+        .json(&json!({ "cmd": method, "params": params }))
+        .send()
+        .await
+        .map_err(|e| Error::CdpConnectionFailed(e.to_string()))?
+        .json::<Value>()
+        .await
+        .map_err(|e| Error::CdpConnectionFailed(e.to_string()))?;
+
+    let value = response.get("value").cloned().unwrap_or(Value::Null);
+
+    // check for error:
+    if let Some(error) = value.get("error").and_then(|e| e.as_str()) {
+        return Err(Error::CdpCommandFailed(error_payload(error, &value)).into());
+    }
+    if let Some(error) = response.get("error") {
+        return Err(Error::CdpCommandFailed(error.clone()).into());
+    }
+
+    Ok(value)
+}
+
+/// Converts the typed command to parameters
+pub(crate) fn params<C: Command>(command: &C) -> Result<Value> {
+    Ok(serde_json::to_value(command)?)
+}
+
+/// Parses the typed command result (an empty result can be 'null')
+pub(crate) fn response<C: Command>(value: Value) -> Result<C::Response> {
+    let value = if value.is_null() { json!({}) } else { value };
+    Ok(serde_json::from_value(value)?)
+}
+
+/// Extracts the CDP error payload (e.g. '{"code":-32601,"message":"..."}') from the WebDriver error
+fn error_payload(error: &str, value: &Value) -> Value {
+    let message = value["message"].as_str().unwrap_or_default();
+
+    message.find('{')
+        .and_then(|i| serde_json::from_str::<Value>(&message[i..]).ok())
+        .unwrap_or_else(|| json!({ "error": error, "message": message }))
+}
+
+/// The CDP command with typed parameters and result
+pub trait Command: Serialize {
+    /// The protocol method name (e.g. 'Page.navigate')
+    const METHOD: &'static str;
+
+    /// The command result
+    type Response: DeserializeOwned;
+}
+
+/// The CDP event with typed parameters
+pub trait Event: DeserializeOwned {
+    /// The protocol method name (e.g. 'Network.responseReceived')
+    const METHOD: &'static str;
+}
+
+// typed domain bindings generated from 'protocol/*.json' (see 'build.rs'):
+include!(concat!(env!("OUT_DIR"), "/cdp.rs"));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn receives_unknown_enum_values_but_never_sends_them() {
+        let known = serde_json::from_value::<Network::ResourceType>(json!("Image")).unwrap();
+        let unknown = serde_json::from_value::<Network::ResourceType>(json!("SomeFutureType")).unwrap();
+
+        assert_eq!(serde_json::to_value(known).unwrap(), json!("Image"));
+        assert_eq!(unknown, Network::ResourceType::Unknown);
+        assert!(serde_json::to_value(unknown).is_err());
+    }
+}
@@ -0,0 +1,307 @@
+use crate::prelude::*;
+use crate::cdp::{ CdpSession, EventStream, RawEvent };
+
+use futures::StreamExt;
+use serde_json::Value;
+use tokio::task::JoinHandle;
+
+/// The console message level
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ConsoleLevel {
+    Debug,
+    Info,
+    Log,
+    Warning,
+    Error,
+}
+
+impl ConsoleLevel {
+    /// Converts the CDP 'consoleAPICalled' type (e.g. 'warning', 'assert')
+    fn from_cdp(kind: &str) -> Self {
+        match kind {
+            "debug" | "trace" => Self::Debug,
+            "info" => Self::Info,
+            "warning" => Self::Warning,
+            "error" | "assert" => Self::Error,
+            _ => Self::Log,
+        }
+    }
+
+    /// Converts the WebDriver log level (e.g. 'SEVERE')
+    fn from_webdriver(level: &str) -> Self {
+        match level {
+            "SEVERE" => Self::Error,
+            "WARNING" => Self::Warning,
+            "INFO" => Self::Info,
+            "DEBUG" | "FINE" | "FINER" | "FINEST" => Self::Debug,
+            _ => Self::Log,
+        }
+    }
+}
+
+/// The console message source
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConsoleSource {
+    /// The 'console.*' call
+    Console,
+    /// The uncaught JavaScript exception
+    Exception,
+    /// The browser log entry (network errors, violations, etc.)
+    Browser,
+}
+
+/// The console message or JavaScript error
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConsoleMessage {
+    pub level: ConsoleLevel,
+    pub source: ConsoleSource,
+    pub text: String,
+    /// The source script URL
+    pub url: Option<String>,
+    /// The source line (1-based)
+    pub line: Option<u32>,
+    /// The source column (1-based)
+    pub column: Option<u32>,
+    /// The message time (UNIX ms)
+    pub timestamp: f64,
+}
+
+impl ConsoleMessage {
+    /// Returns true if it's an error or an uncaught exception
+    pub fn is_error(&self) -> bool {
+        self.level == ConsoleLevel::Error
+    }
+
+    /// Parses the CDP 'Runtime.consoleAPICalled' or 'Runtime.exceptionThrown' event
+    pub(crate) fn from_cdp(event: &RawEvent) -> Option<Self> {
+        let params = &event.params;
+
+        match event.method.as_str() {
+            "Runtime.consoleAPICalled" => {
+                let text = params["args"].as_array()
+                    .map(|args| args.iter().map(remote_object_text).collect::<Vec<_>>().join(" "))
+                    .unwrap_or_default();
+                let frame = &params["stackTrace"]["callFrames"][0];
+
+                Some(Self {
+                    level: ConsoleLevel::from_cdp(params["type"].as_str().unwrap_or_default()),
+                    source: ConsoleSource::Console,
+                    text,
+                    url: frame["url"].as_str().filter(|url| !url.is_empty()).map(|url| url.to_owned()),
+                    line: frame["lineNumber"].as_u64().map(|n| n as u32 + 1),
+                    column: frame["columnNumber"].as_u64().map(|n| n as u32 + 1),
+                    timestamp: params["timestamp"].as_f64().unwrap_or_default(),
+                })
+            },
+
+            "Runtime.exceptionThrown" => {
+                let details = &params["exceptionDetails"];
+                let text = details["exception"]["description"].as_str()
+                    .or_else(|| details["text"].as_str())
+                    .unwrap_or_default()
+                    .to_owned();
+
+                Some(Self {
+                    level: ConsoleLevel::Error,
+                    source: ConsoleSource::Exception,
+                    text,
+                    url: details["url"].as_str().map(|url| url.to_owned()),
+                    line: details["lineNumber"].as_u64().map(|n| n as u32 + 1),
+                    column: details["columnNumber"].as_u64().map(|n| n as u32 + 1),
+                    timestamp: params["timestamp"].as_f64().unwrap_or_default(),
+                })
+            },
+
+            _ => None,
+        }
+    }
+
+    /// Parses the WebDriver browser log entry (the message is formatted as 'url line:column text')
+    pub(crate) fn from_browser_log(entry: &Value) -> Option<Self> {
+        let message = entry["message"].as_str()?;
+        let (mut url, mut line, mut column, mut text) = (None, None, None, message);
+
+        let mut parts = message.splitn(3, ' ');
+        if let (Some(source), Some(position), Some(rest)) = (parts.next(), parts.next(), parts.next())
+            && let Some((l, c)) = position.split_once(':')
+            && let (Ok(l), Ok(c)) = (l.parse::<u32>(), c.parse::<u32>())
+        {
+            url = Some(source.to_owned());
+            (line, column) = (Some(l), Some(c));
+            text = rest;
+        }
+
+        // console calls are quoted, exceptions are not:
+        let source = match entry["source"].as_str() {
+            Some("console-api") => ConsoleSource::Console,
+            Some("javascript") => ConsoleSource::Exception,
+            _ => ConsoleSource::Browser,
+        };
+        let text = match source {
+            ConsoleSource::Console => text.strip_prefix('"').and_then(|t| t.strip_suffix('"')).unwrap_or(text),
+            _ => text,
+        };
+
+        Some(Self {
+            level: ConsoleLevel::from_webdriver(entry["level"].as_str().unwrap_or_default()),
+            source,
+            text: text.to_owned(),
+            url,
+            line,
+            column,
+            timestamp: entry["timestamp"].as_f64().unwrap_or_default(),
+        })
+    }
+}
+
+/// Returns the text of the CDP remote object (the console call argument)
+fn remote_object_text(object: &Value) -> String {
+    match &object["value"] {
+        Value::String(text) => text.clone(),
+        Value::Null => object["description"].as_str()
+            .or_else(|| object["unserializableValue"].as_str())
+            .or_else(|| object["type"].as_str().filter(|t| *t == "undefined"))
+            .unwrap_or("null")
+            .to_owned(),
+        value => value.to_string(),
+    }
+}
+
+/// Returns the stream of the tab console messages & JavaScript errors
+pub(crate) async fn messages(cdp: &CdpSession) -> Result<EventStream<ConsoleMessage>> {
+    Ok(cdp.subscribe(&["Runtime"]).await?
+        .filter_map(|event| async move { ConsoleMessage::from_cdp(&event) })
+        .boxed())
+}
+
+/// The tab console messages buffer
+pub(crate) struct ConsoleCollector {
+    messages: Arc<std::sync::Mutex<Vec<ConsoleMessage>>>,
+    task: JoinHandle<()>,
+}
+
+impl ConsoleCollector {
+    /// Starts collecting the tab console messages
+    /// * previous: the collector stopped with the lost connection (its messages are kept)
+    pub(crate) async fn start(cdp: &CdpSession, previous: Option<&ConsoleCollector>) -> Result<Self> {
+        let mut stream = messages(cdp).await?;
+        let messages = previous.map(|p| p.messages.clone()).unwrap_or_default();
+
+        let task = {
+            let messages = messages.clone();
+            tokio::spawn(async move {
+                while let Some(message) = stream.next().await {
+                    messages.lock().unwrap().push(message);
+                }
+            })
+        };
+
+        Ok(Self { messages, task })
+    }
+
+    /// Returns true if the collecting is stopped (the events stream is ended with the lost connection)
+    pub(crate) fn is_stopped(&self) -> bool {
+        self.task.is_finished()
+    }
+
+    /// Returns the collected messages
+    pub(crate) fn messages(&self) -> Vec<ConsoleMessage> {
+        self.messages.lock().unwrap().clone()
+    }
+
+    /// Clears the collected messages
+    pub(crate) fn clear(&self) {
+        self.messages.lock().unwrap().clear();
+    }
+}
+
+impl Drop for ConsoleCollector {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn event(method: &str, params: Value) -> RawEvent {
+        RawEvent { method: method.to_owned(), params }
+    }
+
+    #[test]
+    fn parses_console_api_calls() {
+        let message = ConsoleMessage::from_cdp(&event("Runtime.consoleAPICalled", json!({
+            "type": "warning",
+            "args": [
+                { "type": "string", "value": "count:" },
+                { "type": "number", "value": 2 },
+                { "type": "undefined" },
+                { "type": "object", "subtype": "null", "value": null },
+                { "type": "number", "unserializableValue": "NaN" },
+                { "type": "object", "description": "Array(2)" },
+            ],
+            "stackTrace": { "callFrames": [{ "url": "https://example.com/app.js", "lineNumber": 9, "columnNumber": 4 }] },
+            "timestamp": 1700000000000.0,
+        }))).unwrap();
+
+        assert_eq!(message.level, ConsoleLevel::Warning);
+        assert_eq!(message.source, ConsoleSource::Console);
+        assert_eq!(message.text, "count: 2 undefined null NaN Array(2)");
+        assert_eq!(message.url.as_deref(), Some("https://example.com/app.js"));
+        assert_eq!((message.line, message.column), (Some(10), Some(5)));
+        assert!(!message.is_error());
+
+        let assert = ConsoleMessage::from_cdp(&event("Runtime.consoleAPICalled", json!({ "type": "assert", "args": [] }))).unwrap();
+        assert!(assert.is_error() && assert.url.is_none());
+    }
+
+    #[test]
+    fn parses_thrown_exceptions() {
+        let message = ConsoleMessage::from_cdp(&event("Runtime.exceptionThrown", json!({
+            "timestamp": 5.0,
+            "exceptionDetails": {
+                "text": "Uncaught",
+                "url": "https://example.com/app.js",
+                "lineNumber": 0,
+                "columnNumber": 12,
+                "exception": { "description": "TypeError: x is undefined" },
+            },
+        }))).unwrap();
+
+        assert_eq!((message.level, message.source), (ConsoleLevel::Error, ConsoleSource::Exception));
+        assert_eq!(message.text, "TypeError: x is undefined");
+        assert_eq!((message.line, message.column), (Some(1), Some(13)));
+
+        let message = ConsoleMessage::from_cdp(&event("Runtime.exceptionThrown", json!({ "exceptionDetails": { "text": "Uncaught" } }))).unwrap();
+        assert_eq!(message.text, "Uncaught");
+        assert!(ConsoleMessage::from_cdp(&event("Runtime.executionContextCreated", json!({}))).is_none());
+    }
+
+    #[test]
+    fn parses_browser_log_entries() {
+        let message = ConsoleMessage::from_browser_log(&json!({
+            "level": "INFO",
+            "source": "console-api",
+            "message": "https://example.com/app.js 10:5 \"hello world\"",
+            "timestamp": 1700000000000.0,
+        })).unwrap();
+
+        assert_eq!((message.level, message.source), (ConsoleLevel::Info, ConsoleSource::Console));
+        assert_eq!(message.text, "hello world");
+        assert_eq!(message.url.as_deref(), Some("https://example.com/app.js"));
+        assert_eq!((message.line, message.column), (Some(10), Some(5)));
+
+        let message = ConsoleMessage::from_browser_log(&json!({
+            "level": "SEVERE",
+            "source": "network",
+            "message": "Failed to load resource: net::ERR_NAME_NOT_RESOLVED",
+        })).unwrap();
+
+        assert_eq!((message.level, message.source), (ConsoleLevel::Error, ConsoleSource::Browser));
+        assert_eq!(message.text, "Failed to load resource: net::ERR_NAME_NOT_RESOLVED");
+        assert!(message.url.is_none() && message.line.is_none());
+        assert!(ConsoleMessage::from_browser_log(&json!({ "level": "INFO" })).is_none());
+    }
+}
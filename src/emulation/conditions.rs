@@ -0,0 +1,88 @@
+use crate::prelude::*;
+use crate::cdp::Network;
+
+/// The emulated network conditions
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NetworkConditions {
+    /// Emulates the network disconnection
+    pub offline: bool,
+    /// The minimum request latency
+    pub latency: Duration,
+    /// The max download throughput (bytes/sec, None = unlimited)
+    pub download: Option<u64>,
+    /// The max upload throughput (bytes/sec, None = unlimited)
+    pub upload: Option<u64>,
+}
+
+impl Default for NetworkConditions {
+    fn default() -> Self {
+        Self::none()
+    }
+}
+
+impl NetworkConditions {
+    /// No throttling (disables the emulation)
+    pub fn none() -> Self {
+        Self {
+            offline: false,
+            latency: Duration::ZERO,
+            download: None,
+            upload: None,
+        }
+    }
+
+    /// The network disconnection
+    pub fn offline() -> Self {
+        Self { offline: true, ..Self::none() }
+    }
+
+    /// The DevTools 'Slow 3G' preset
+    pub fn slow_3g() -> Self {
+        Self {
+            offline: false,
+            latency: Duration::from_millis(2000),
+            download: Some(50_000),
+            upload: Some(50_000),
+        }
+    }
+
+    /// The DevTools 'Fast 3G' preset
+    pub fn fast_3g() -> Self {
+        Self {
+            offline: false,
+            latency: Duration::from_micros(562_500),
+            download: Some(180_000),
+            upload: Some(84_375),
+        }
+    }
+
+    /// Sets the minimum request latency
+    pub fn latency(mut self, latency: Duration) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    /// Sets the max download throughput (bytes/sec)
+    pub fn download(mut self, bytes_per_sec: u64) -> Self {
+        self.download = Some(bytes_per_sec);
+        self
+    }
+
+    /// Sets the max upload throughput (bytes/sec)
+    pub fn upload(mut self, bytes_per_sec: u64) -> Self {
+        self.upload = Some(bytes_per_sec);
+        self
+    }
+
+    /// Converts to the CDP command
+    pub(crate) fn to_command(self) -> Network::EmulateNetworkConditions {
+        Network::EmulateNetworkConditions {
+            offline: self.offline,
+            latency: self.latency.as_secs_f64() * 1000.0,
+            // -1 disables the throttling:
+            download_throughput: self.download.map(|b| b as f64).unwrap_or(-1.0),
+            upload_throughput: self.upload.map(|b| b as f64).unwrap_or(-1.0),
+            ..Default::default()
+        }
+    }
+}
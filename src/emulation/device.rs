@@ -0,0 +1,138 @@
+use crate::cdp::Emulation;
+
+use serde_json::{ json, Value };
+
+const ANDROID_UA: &str = "Mozilla/5.0 (Linux; Android 13; {model}) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Mobile Safari/537.36";
+const IPHONE_UA: &str = "Mozilla/5.0 (iPhone; CPU iPhone OS 16_6 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/16.6 Mobile/15E148 Safari/604.1";
+const IPAD_UA: &str = "Mozilla/5.0 (iPad; CPU OS 16_6 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/16.6 Mobile/15E148 Safari/604.1";
+
+/// The preset row (name, width, height, scale factor, mobile, user agent)
+type Preset = (&'static str, u32, u32, f64, bool, Option<&'static str>);
+
+/// The built-in presets
+const PRESETS: &[Preset] = &[
+    ("Pixel 5", 393, 851, 2.75, true, Some(ANDROID_UA)),
+    ("Pixel 7", 412, 915, 2.625, true, Some(ANDROID_UA)),
+    ("Galaxy S20 Ultra", 412, 915, 3.5, true, Some(ANDROID_UA)),
+    ("iPhone SE", 375, 667, 2.0, true, Some(IPHONE_UA)),
+    ("iPhone 12 Pro", 390, 844, 3.0, true, Some(IPHONE_UA)),
+    ("iPhone 14 Pro Max", 430, 932, 3.0, true, Some(IPHONE_UA)),
+    ("iPad Mini", 768, 1024, 2.0, true, Some(IPAD_UA)),
+    ("iPad Air", 820, 1180, 2.0, true, Some(IPAD_UA)),
+    ("iPad Pro", 1024, 1366, 2.0, true, Some(IPAD_UA)),
+    ("Laptop", 1280, 800, 1.0, false, None),
+    ("Desktop HD", 1366, 768, 1.0, false, None),
+    ("Desktop FHD", 1920, 1080, 1.0, false, None),
+    ("Desktop QHD", 2560, 1440, 1.0, false, None),
+];
+
+/// The emulated device (viewport, pixel ratio, touch & user agent)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Device {
+    pub name: String,
+    /// The viewport width (CSS pixels)
+    pub width: u32,
+    /// The viewport height (CSS pixels)
+    pub height: u32,
+    /// The device pixel ratio
+    pub scale_factor: f64,
+    /// Emulates the mobile viewport (meta viewport, overlay scrollbars)
+    pub mobile: bool,
+    /// Emulates the touch screen
+    pub touch: bool,
+    /// The user agent (None = the session default one)
+    pub user_agent: Option<String>,
+}
+
+impl Device {
+    /// Creates the custom desktop device
+    pub fn new<S: Into<String>>(name: S, width: u32, height: u32) -> Self {
+        Self {
+            name: name.into(),
+            width,
+            height,
+            scale_factor: 1.0,
+            mobile: false,
+            touch: false,
+            user_agent: None,
+        }
+    }
+
+    /// Returns the built-in preset by name (case-insensitive, e.g. 'Pixel 7', 'iPhone SE', 'Desktop FHD')
+    pub fn preset(name: &str) -> Option<Self> {
+        Self::presets().into_iter().find(|device| device.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Returns all built-in presets
+    pub fn presets() -> Vec<Self> {
+        PRESETS.iter()
+            .map(|(name, width, height, scale_factor, mobile, user_agent)| Self {
+                name: name.to_string(),
+                width: *width,
+                height: *height,
+                scale_factor: *scale_factor,
+                mobile: *mobile,
+                touch: *mobile,
+                user_agent: user_agent.map(|ua| ua.replace("{model}", name)),
+            })
+            .collect()
+    }
+
+    /// Sets the device pixel ratio
+    pub fn scale_factor(mut self, scale_factor: f64) -> Self {
+        self.scale_factor = scale_factor;
+        self
+    }
+
+    /// Emulates the mobile device with the touch screen
+    pub fn mobile(mut self, mobile: bool) -> Self {
+        self.mobile = mobile;
+        self.touch = mobile;
+        self
+    }
+
+    /// Sets the user agent
+    pub fn user_agent<S: Into<String>>(mut self, user_agent: S) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Swaps the width & height
+    pub fn landscape(mut self) -> Self {
+        if self.height > self.width {
+            (self.width, self.height) = (self.height, self.width);
+        }
+        self
+    }
+
+    /// Converts to the CDP metrics override
+    pub(crate) fn to_metrics(&self) -> Emulation::SetDeviceMetricsOverride {
+        Emulation::SetDeviceMetricsOverride {
+            width: self.width as i64,
+            height: self.height as i64,
+            device_scale_factor: self.scale_factor,
+            mobile: self.mobile,
+            screen_width: Some(self.width as i64),
+            screen_height: Some(self.height as i64),
+            ..Default::default()
+        }
+    }
+
+    /// Converts to the chromedriver 'mobileEmulation' option
+    pub(crate) fn to_mobile_emulation(&self) -> Value {
+        let mut emulation = json!({
+            "deviceMetrics": {
+                "width": self.width,
+                "height": self.height,
+                "pixelRatio": self.scale_factor,
+                "mobile": self.mobile,
+                "touch": self.touch,
+            }
+        });
+        if let Some(user_agent) = &self.user_agent {
+            emulation["userAgent"] = json!(user_agent);
+        }
+
+        emulation
+    }
+}
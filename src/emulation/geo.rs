@@ -0,0 +1,27 @@
+use crate::cdp::Emulation;
+
+/// The emulated geolocation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Geolocation {
+    pub latitude: f64,
+    pub longitude: f64,
+    /// The position accuracy (meters)
+    pub accuracy: f64,
+}
+
+impl Geolocation {
+    /// Creates the geolocation
+    pub fn new(latitude: f64, longitude: f64, accuracy: f64) -> Self {
+        Self { latitude, longitude, accuracy }
+    }
+
+    /// Converts to the CDP override
+    pub(crate) fn to_command(self) -> Emulation::SetGeolocationOverride {
+        Emulation::SetGeolocationOverride {
+            latitude: Some(self.latitude),
+            longitude: Some(self.longitude),
+            accuracy: Some(self.accuracy),
+            ..Default::default()
+        }
+    }
+}
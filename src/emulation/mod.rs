@@ -0,0 +1,4 @@
+pub mod user_agent;  pub use user_agent::UserAgent;
+pub mod device;      pub use device::Device;
+pub mod geo;         pub use geo::Geolocation;
+pub mod conditions;  pub use conditions::NetworkConditions;
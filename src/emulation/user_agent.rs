@@ -0,0 +1,53 @@
+use crate::cdp::Emulation::{ self, UserAgentMetadata };
+
+/// The user agent override
+#[derive(Debug, Clone, PartialEq)]
+pub struct UserAgent {
+    pub user_agent: String,
+    /// The 'Accept-Language' header & 'navigator.languages' (e.g. 'en-US,en')
+    pub accept_language: Option<String>,
+    /// The 'navigator.platform' value (e.g. 'Win32')
+    pub platform: Option<String>,
+    /// The User-Agent Client Hints ('Sec-CH-UA-*' headers & 'navigator.userAgentData')
+    pub client_hints: Option<UserAgentMetadata>,
+}
+
+impl UserAgent {
+    /// Creates the user agent override
+    pub fn new<S: Into<String>>(user_agent: S) -> Self {
+        Self {
+            user_agent: user_agent.into(),
+            accept_language: None,
+            platform: None,
+            client_hints: None,
+        }
+    }
+
+    /// Sets the accepted languages (e.g. 'en-US,en')
+    pub fn accept_language<S: Into<String>>(mut self, languages: S) -> Self {
+        self.accept_language = Some(languages.into());
+        self
+    }
+
+    /// Sets the 'navigator.platform' value
+    pub fn platform<S: Into<String>>(mut self, platform: S) -> Self {
+        self.platform = Some(platform.into());
+        self
+    }
+
+    /// Sets the User-Agent Client Hints
+    pub fn client_hints(mut self, metadata: UserAgentMetadata) -> Self {
+        self.client_hints = Some(metadata);
+        self
+    }
+
+    /// Converts to the CDP command
+    pub(crate) fn to_command(&self) -> Emulation::SetUserAgentOverride {
+        Emulation::SetUserAgentOverride {
+            user_agent: self.user_agent.clone(),
+            accept_language: self.accept_language.clone(),
+            platform: self.platform.clone(),
+            user_agent_metadata: self.client_hints.clone(),
+        }
+    }
+}
@@ -24,11 +24,17 @@ pub enum Error {
     #[display = "No window handles found"]
     NoWindowHandles,
 
-    #[display = "Failed to connect to CDP (Chrome DevTools Protocol)"]
-    CdpConnectionFailed,
+    #[display = "Failed to connect to CDP (Chrome DevTools Protocol): {0}"]
+    CdpConnectionFailed(String),
 
-    #[display = "CDP command execution failed"]
-    CdpCommandFailed,
+    #[display = "CDP command execution failed: {0}"]
+    CdpCommandFailed(serde_json::Value),
+
+    #[display = "WebSocket connection failed: {0}"]
+    WebSocketFailed(String),
+
+    #[display = "WebDriver BiDi command failed: {0}"]
+    BiDiCommandFailed(serde_json::Value),
 
     #[display = "Element not found for the given selector"]
     ElementNotFound,
@@ -38,4 +44,28 @@ pub enum Error {
 
     #[display = "Unexpected script response value"]
     UnexpectedResponse,
+
+    #[display = "Timed out waiting for the page condition"]
+    WaitTimeout,
+
+    #[display = "Invalid or unsupported image data"]
+    InvalidImage,
+
+    #[display = "The response body is not captured (e.g. evicted from the browser buffer or a redirect)"]
+    ResponseBodyUnavailable,
+
+    #[display = "The proxy authentication extension is not loaded in the headless mode, use 'ProxyAuth::Fetch'"]
+    ProxyExtensionHeadless,
+
+    #[display = "The HAR recording is not started on the tab"]
+    HarNotRecording,
+
+    #[display = "The tab is not on the storage snapshot origin '{0}'"]
+    StorageOriginMismatch(String),
+
+    #[display = "The web storage is not available on the tab origin (e.g. 'about:blank' or an opaque origin): {0}"]
+    StorageUnavailable(String),
+
+    #[display = "WebDriver command failed: {0}"]
+    CommandFailed(String),
 }
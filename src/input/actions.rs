@@ -0,0 +1,393 @@
+use crate::prelude::*;
+use crate::Tab;
+use super::Key;
+
+use std::collections::HashMap;
+use serde_json::{ json, Value };
+
+/// The mouse button
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MouseButton {
+    Left,
+    Middle,
+    Right,
+    Back,
+    Forward,
+}
+
+impl MouseButton {
+    /// Returns the W3C button code
+    pub fn code(&self) -> u8 {
+        match self {
+            Self::Left => 0,
+            Self::Middle => 1,
+            Self::Right => 2,
+            Self::Back => 3,
+            Self::Forward => 4,
+        }
+    }
+}
+
+/// The origin of pointer coordinates
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Origin {
+    /// Coordinates are relative to the top-left corner of the viewport
+    Viewport,
+    /// Coordinates are relative to the current pointer position
+    Pointer,
+    /// Coordinates are relative to the center of the element (CSS selector)
+    Element(String),
+}
+
+/// The input source that performs an action
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Source {
+    Keyboard,
+    Mouse,
+    Pen,
+    Wheel,
+}
+
+impl Source {
+    const ALL: [Source; 4] = [Source::Keyboard, Source::Mouse, Source::Pen, Source::Wheel];
+
+    /// Returns the input source description
+    fn describe(&self) -> Value {
+        match self {
+            Self::Keyboard => json!({ "type": "key", "id": "keyboard" }),
+            Self::Mouse => json!({ "type": "pointer", "id": "mouse", "parameters": { "pointerType": "mouse" } }),
+            Self::Pen => json!({ "type": "pointer", "id": "pen", "parameters": { "pointerType": "pen" } }),
+            Self::Wheel => json!({ "type": "wheel", "id": "wheel" }),
+        }
+    }
+}
+
+/// The single tick of the actions sequence
+#[derive(Debug, Clone)]
+enum Tick {
+    /// One input source acts, the others are paused
+    Action { source: Source, action: Value, element: Option<String> },
+    /// All input sources are paused
+    Pause(u64),
+}
+
+/// The W3C actions builder (keyboard, mouse, pen and wheel input sequences)
+#[derive(Clone)]
+pub struct Actions<'a> {
+    tab: &'a Tab,
+    ticks: Vec<Tick>,
+}
+
+impl<'a> Actions<'a> {
+    /// Creates a new empty actions sequence for the tab
+    pub fn new(tab: &'a Tab) -> Self {
+        Self {
+            tab,
+            ticks: vec![],
+        }
+    }
+
+    /// Returns the number of ticks in the sequence
+    pub fn len(&self) -> usize {
+        self.ticks.len()
+    }
+
+    /// Returns true if the sequence has no ticks
+    pub fn is_empty(&self) -> bool {
+        self.ticks.is_empty()
+    }
+
+    /// Appends a tick for the input source
+    fn push(mut self, source: Source, action: Value) -> Self {
+        self.ticks.push(Tick::Action { source, action, element: None });
+        self
+    }
+
+    /// Appends a tick for the input source with an element origin
+    fn push_origin(mut self, source: Source, mut action: Value, origin: Origin) -> Self {
+        let element = match origin {
+            Origin::Viewport => { action["origin"] = json!("viewport"); None },
+            Origin::Pointer => { action["origin"] = json!("pointer"); None },
+            Origin::Element(selector) => Some(selector),
+        };
+
+        self.ticks.push(Tick::Action { source, action, element });
+        self
+    }
+
+    /// Pauses all input sources
+    pub fn pause(mut self, duration: Duration) -> Self {
+        self.ticks.push(Tick::Pause(duration.as_millis() as u64));
+        self
+    }
+
+    // ---- keyboard ----
+
+    /// Presses the key down
+    pub fn key_down<K: Into<Key>>(self, key: K) -> Self {
+        let key = key.into().codepoint().to_string();
+        self.push(Source::Keyboard, json!({ "type": "keyDown", "value": key }))
+    }
+
+    /// Releases the key
+    pub fn key_up<K: Into<Key>>(self, key: K) -> Self {
+        let key = key.into().codepoint().to_string();
+        self.push(Source::Keyboard, json!({ "type": "keyUp", "value": key }))
+    }
+
+    /// Presses and releases the key
+    pub fn press<K: Into<Key>>(self, key: K) -> Self {
+        let key = key.into();
+        self.key_down(key).key_up(key)
+    }
+
+    /// Types the text char by char
+    pub fn send_keys(mut self, text: &str) -> Self {
+        for c in text.chars() {
+            self = self.press(c);
+        }
+        self
+    }
+
+    /// Presses the keys in order and releases them in reverse order (e.g. Ctrl+Shift+T)
+    pub fn key_chord(mut self, keys: &[Key]) -> Self {
+        for key in keys {
+            self = self.key_down(*key);
+        }
+        for key in keys.iter().rev() {
+            self = self.key_up(*key);
+        }
+        self
+    }
+
+    // ---- mouse ----
+
+    /// Moves the mouse pointer relative to the origin
+    pub fn pointer_move(self, origin: Origin, x: i64, y: i64, duration: Duration) -> Self {
+        let action = json!({ "type": "pointerMove", "x": x, "y": y, "duration": duration.as_millis() as u64 });
+        self.push_origin(Source::Mouse, action, origin)
+    }
+
+    /// Moves the mouse pointer to the viewport coordinates
+    pub fn move_to(self, x: i64, y: i64) -> Self {
+        self.pointer_move(Origin::Viewport, x, y, Duration::ZERO)
+    }
+
+    /// Moves the mouse pointer by the offset from its current position
+    pub fn move_by(self, dx: i64, dy: i64) -> Self {
+        self.pointer_move(Origin::Pointer, dx, dy, Duration::ZERO)
+    }
+
+    /// Moves the mouse pointer to the center of the element
+    pub fn move_to_element<S: Into<String>>(self, selector: S) -> Self {
+        self.pointer_move(Origin::Element(selector.into()), 0, 0, Duration::ZERO)
+    }
+
+    /// Hovers the element (alias of 'move_to_element')
+    pub fn hover<S: Into<String>>(self, selector: S) -> Self {
+        self.move_to_element(selector)
+    }
+
+    /// Presses the mouse button down
+    pub fn pointer_down(self, button: MouseButton) -> Self {
+        self.push(Source::Mouse, json!({ "type": "pointerDown", "button": button.code() }))
+    }
+
+    /// Releases the mouse button
+    pub fn pointer_up(self, button: MouseButton) -> Self {
+        self.push(Source::Mouse, json!({ "type": "pointerUp", "button": button.code() }))
+    }
+
+    /// Clicks the mouse button at the current pointer position
+    pub fn click_button(self, button: MouseButton) -> Self {
+        self.pointer_down(button).pointer_up(button)
+    }
+
+    /// Clicks the left mouse button
+    pub fn click(self) -> Self {
+        self.click_button(MouseButton::Left)
+    }
+
+    /// Double clicks the left mouse button
+    pub fn double_click(self) -> Self {
+        self.click().click()
+    }
+
+    /// Clicks the right mouse button
+    pub fn context_click(self) -> Self {
+        self.click_button(MouseButton::Right)
+    }
+
+    /// Drags the source element and drops it on the target element
+    pub fn drag_and_drop<S: Into<String>, T: Into<String>>(self, source: S, target: T) -> Self {
+        self.move_to_element(source)
+            .pointer_down(MouseButton::Left)
+            .pointer_move(Origin::Element(target.into()), 0, 0, Duration::from_millis(250))
+            .pointer_up(MouseButton::Left)
+    }
+
+    /// Drags the element by the offset
+    pub fn drag_and_drop_by<S: Into<String>>(self, source: S, dx: i64, dy: i64) -> Self {
+        self.move_to_element(source)
+            .pointer_down(MouseButton::Left)
+            .pointer_move(Origin::Pointer, dx, dy, Duration::from_millis(250))
+            .pointer_up(MouseButton::Left)
+    }
+
+    // ---- pen ----
+
+    /// Moves the pen relative to the origin
+    pub fn pen_move(self, origin: Origin, x: i64, y: i64, duration: Duration) -> Self {
+        let action = json!({ "type": "pointerMove", "x": x, "y": y, "duration": duration.as_millis() as u64 });
+        self.push_origin(Source::Pen, action, origin)
+    }
+
+    /// Touches the surface by the pen with pressure (0.0 - 1.0)
+    pub fn pen_down(self, pressure: f64) -> Self {
+        self.push(Source::Pen, json!({ "type": "pointerDown", "button": 0, "pressure": pressure.clamp(0.0, 1.0) }))
+    }
+
+    /// Lifts the pen up
+    pub fn pen_up(self) -> Self {
+        self.push(Source::Pen, json!({ "type": "pointerUp", "button": 0 }))
+    }
+
+    // ---- wheel ----
+
+    /// Scrolls the wheel at the viewport coordinates
+    pub fn scroll(self, x: i64, y: i64, dx: i64, dy: i64) -> Self {
+        let action = json!({ "type": "scroll", "x": x, "y": y, "deltaX": dx, "deltaY": dy, "duration": 0 });
+        self.push_origin(Source::Wheel, action, Origin::Viewport)
+    }
+
+    /// Scrolls the wheel over the element
+    pub fn scroll_element<S: Into<String>>(self, selector: S, dx: i64, dy: i64) -> Self {
+        let action = json!({ "type": "scroll", "x": 0, "y": 0, "deltaX": dx, "deltaY": dy, "duration": 0 });
+        self.push_origin(Source::Wheel, action, Origin::Element(selector.into()))
+    }
+
+    // ---- performing ----
+
+    /// Builds the W3C actions list, resolving element origins
+    async fn build(&self) -> Result<Vec<Value>> {
+        let mut elements = HashMap::new();
+        for tick in &self.ticks {
+            if let Tick::Action { element: Some(selector), .. } = tick && !elements.contains_key(selector) {
+                let id = self.tab.find_element(selector).await?;
+                elements.insert(selector.clone(), id);
+            }
+        }
+
+        Ok(self.sources(&elements))
+    }
+
+    /// Returns the W3C actions list of the used input sources
+    /// * elements: the element references by CSS selectors
+    fn sources(&self, elements: &HashMap<String, Value>) -> Vec<Value> {
+        let mut sources = vec![];
+
+        for source in Source::ALL {
+            if !self.ticks.iter().any(|tick| matches!(tick, Tick::Action { source: s, .. } if *s == source)) {
+                continue;
+            }
+
+            let actions = self.ticks.iter()
+                .map(|tick| match tick {
+                    Tick::Action { source: s, action, element } if *s == source => {
+                        let mut action = action.clone();
+                        if let Some(selector) = element {
+                            action["origin"] = elements.get(selector).cloned().unwrap_or(Value::Null);
+                        }
+                        action
+                    },
+                    Tick::Action { .. } => json!({ "type": "pause", "duration": 0 }),
+                    Tick::Pause(ms) => json!({ "type": "pause", "duration": ms }),
+                })
+                .collect::<Vec<_>>();
+
+            let mut desc = source.describe();
+            desc["actions"] = json!(actions);
+            sources.push(desc);
+        }
+
+        sources
+    }
+
+    /// Performs the actions sequence on the tab
+    pub async fn perform(self) -> Result<()> {
+        if self.ticks.is_empty() { return Ok(()); }
+
+        let tab = self.tab;
+        tab.locked(async {
+            let actions = self.build().await?;
+            tab.command(Method::POST, "actions", Some(json!({ "actions": actions }))).await?;
+            Ok(())
+        }).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::{ SessionManager, Shared };
+
+    fn tab() -> Tab {
+        Tab {
+            client: reqwest::Client::new(),
+            port: 9515,
+            session_id: str!("session"),
+            tab_id: str!("tab"),
+            url: String::new(),
+            manager: Arc::new(SessionManager::new()),
+            shared: Arc::new(Shared::new(None, None)),
+        }
+    }
+
+    #[test]
+    fn builds_w3c_ticks_per_source() {
+        let tab = tab();
+        let actions = Actions::new(&tab)
+            .move_to(10, 20)
+            .key_down(Key::Shift)
+            .pause(Duration::from_millis(50))
+            .click()
+            .key_up(Key::Shift);
+
+        assert_eq!(actions.len(), 6);
+        assert_eq!(actions.sources(&HashMap::new()), vec![
+            json!({ "type": "key", "id": "keyboard", "actions": [
+                { "type": "pause", "duration": 0 },
+                { "type": "keyDown", "value": "\u{E008}" },
+                { "type": "pause", "duration": 50 },
+                { "type": "pause", "duration": 0 },
+                { "type": "pause", "duration": 0 },
+                { "type": "keyUp", "value": "\u{E008}" },
+            ]}),
+            json!({ "type": "pointer", "id": "mouse", "parameters": { "pointerType": "mouse" }, "actions": [
+                { "type": "pointerMove", "x": 10, "y": 20, "duration": 0, "origin": "viewport" },
+                { "type": "pause", "duration": 0 },
+                { "type": "pause", "duration": 50 },
+                { "type": "pointerDown", "button": 0 },
+                { "type": "pointerUp", "button": 0 },
+                { "type": "pause", "duration": 0 },
+            ]}),
+        ]);
+    }
+
+    #[test]
+    fn resolves_element_origins() {
+        let tab = tab();
+        let element = json!({ "element-6066-11e4-a52e-4f735466cecf": "e1" });
+        let elements = HashMap::from([(str!("#target"), element.clone())]);
+
+        let sources = Actions::new(&tab)
+            .scroll_element("#target", 0, 120)
+            .sources(&elements);
+
+        assert_eq!(sources, vec![
+            json!({ "type": "wheel", "id": "wheel", "actions": [
+                { "type": "scroll", "x": 0, "y": 0, "deltaX": 0, "deltaY": 120, "duration": 0, "origin": element },
+            ]}),
+        ]);
+    }
+}
@@ -0,0 +1,378 @@
+use crate::prelude::*;
+use super::{ Actions, Key, MouseButton, Origin };
+
+use std::time::{ SystemTime, UNIX_EPOCH };
+
+/// The seedable pseudo-random generator (SplitMix64), the same seed gives the same sequence
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    /// Creates a new generator from the seed
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Creates a new generator seeded from the system clock
+    pub fn from_time() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or_default();
+
+        Self::new(nanos)
+    }
+
+    /// Returns the next random number
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a random number in range [0.0, 1.0)
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Returns a random number in range [min, max)
+    pub fn range(&mut self, min: f64, max: f64) -> f64 {
+        min + (max - min) * self.next_f64()
+    }
+
+    /// Returns true with the probability (0.0 - 1.0)
+    pub fn chance(&mut self, probability: f64) -> bool {
+        self.next_f64() < probability
+    }
+
+    /// Returns a random duration in range [min, max]
+    pub fn duration(&mut self, min: Duration, max: Duration) -> Duration {
+        if max <= min { return min; }
+        let ms = self.range(min.as_millis() as f64, max.as_millis() as f64 + 1.0);
+        Duration::from_millis(ms as u64)
+    }
+}
+
+/// The human-like input settings
+#[derive(Debug, Clone)]
+pub struct HumanConfig {
+    /// The pointer speed range (pixels per second)
+    pub speed: (f64, f64),
+    /// The trajectory curvature (0.0 = straight line)
+    pub curvature: f64,
+    /// The maximum pointer deviation from the trajectory (pixels)
+    pub jitter: f64,
+    /// The duration of a single pointer move step
+    pub step: Duration,
+    /// The probability of overshooting the target and correcting back
+    pub overshoot_chance: f64,
+    /// The delay range between keystrokes
+    pub key_delay: (Duration, Duration),
+    /// The probability of a typo followed by a correction
+    pub typo_chance: f64,
+    /// The dwell time range (before clicks and between button press and release)
+    pub dwell: (Duration, Duration),
+}
+
+impl Default for HumanConfig {
+    fn default() -> Self {
+        Self {
+            speed: (600.0, 1400.0),
+            curvature: 0.3,
+            jitter: 1.5,
+            step: Duration::from_millis(16),
+            overshoot_chance: 0.1,
+            key_delay: (Duration::from_millis(60), Duration::from_millis(220)),
+            typo_chance: 0.03,
+            dwell: (Duration::from_millis(50), Duration::from_millis(180)),
+        }
+    }
+}
+
+/// The human-like input simulator (keeps track of the pointer position)
+#[derive(Debug, Clone)]
+pub struct Humanizer {
+    rng: Rng,
+    config: HumanConfig,
+    position: (f64, f64),
+    viewport: Option<(f64, f64)>,
+}
+
+impl Humanizer {
+    /// Creates a new simulator with the default settings
+    pub fn new(seed: u64) -> Self {
+        Self::with_config(seed, HumanConfig::default())
+    }
+
+    /// Creates a new simulator with custom settings
+    pub fn with_config(seed: u64, config: HumanConfig) -> Self {
+        Self {
+            rng: Rng::new(seed),
+            config,
+            position: (0.0, 0.0),
+            viewport: None,
+        }
+    }
+
+    /// Sets the viewport size the trajectories are kept within (CSS pixels)
+    pub fn viewport(mut self, width: u32, height: u32) -> Self {
+        self.set_viewport(width, height);
+        self
+    }
+
+    /// Changes the viewport size the trajectories are kept within (CSS pixels)
+    pub fn set_viewport(&mut self, width: u32, height: u32) {
+        self.viewport = Some((width as f64, height as f64));
+    }
+
+    /// Returns the simulator settings
+    pub fn config(&self) -> &HumanConfig {
+        &self.config
+    }
+
+    /// Returns the random generator
+    pub fn rng(&mut self) -> &mut Rng {
+        &mut self.rng
+    }
+
+    /// Returns the last known pointer position
+    pub fn position(&self) -> (f64, f64) {
+        self.position
+    }
+
+    /// Sets the current pointer position (e.g. after a non-human move)
+    pub fn set_position(&mut self, x: f64, y: f64) {
+        self.position = (x, y);
+    }
+
+    /// Keeps the point within the viewport (negative coordinates are always out of bounds)
+    fn clamp(&self, x: f64, y: f64) -> (f64, f64) {
+        let (x, y) = (x.max(0.0), y.max(0.0));
+
+        match self.viewport {
+            Some((width, height)) => (x.min((width - 1.0).max(0.0)), y.min((height - 1.0).max(0.0))),
+            None => (x, y),
+        }
+    }
+
+    /// Keeps the trajectory point within the viewport, or within the box of the move start & end points if the viewport is unknown
+    fn bound(&self, (x, y): (f64, f64), from: (f64, f64), to: (f64, f64)) -> (f64, f64) {
+        match self.viewport {
+            Some(_) => self.clamp(x, y),
+            None => (x.clamp(from.0.min(to.0), from.0.max(to.0)), y.clamp(from.1.min(to.1), from.1.max(to.1))),
+        }
+    }
+
+    /// Returns a random dwell time
+    pub fn dwell(&mut self) -> Duration {
+        let (min, max) = self.config.dwell;
+        self.rng.duration(min, max)
+    }
+
+    /// Returns a random delay between keystrokes
+    pub fn key_delay(&mut self) -> Duration {
+        let (min, max) = self.config.key_delay;
+        self.rng.duration(min, max)
+    }
+
+    /// Generates a curved pointer trajectory to the point as a list of (x, y, step duration),
+    /// note: all points are kept within the viewport (see 'Humanizer::viewport'), without it the target isn't overshot
+    /// and the points are kept within the box of the current position & the target
+    pub fn path(&mut self, x: f64, y: f64) -> Vec<(i64, i64, Duration)> {
+        let mut points = vec![];
+        let (x, y) = self.clamp(x, y);
+
+        // overshoot the target and come back (the bounds must be known):
+        if self.viewport.is_some() && self.rng.chance(self.config.overshoot_chance) {
+            let (fx, fy) = self.position;
+            let k = self.rng.range(0.03, 0.1);
+            let (ox, oy) = self.clamp(x + (x - fx) * k, y + (y - fy) * k);
+
+            points.extend(self.segment(ox, oy));
+        }
+
+        points.extend(self.segment(x, y));
+        points
+    }
+
+    /// Generates a single cubic Bezier segment from the current position
+    fn segment(&mut self, x: f64, y: f64) -> Vec<(i64, i64, Duration)> {
+        let (fx, fy) = self.position;
+        let (dx, dy) = (x - fx, y - fy);
+        let distance = (dx * dx + dy * dy).sqrt();
+
+        self.position = (x, y);
+        if distance < 1.0 {
+            return vec![(x.round() as i64, y.round() as i64, Duration::ZERO)];
+        }
+
+        // control points are shifted along the normal:
+        let (nx, ny) = (-dy / distance, dx / distance);
+        let bend = distance * self.config.curvature;
+        let (b1, b2) = (self.rng.range(-bend, bend), self.rng.range(-bend, bend));
+        let c1 = (fx + dx * 0.3 + nx * b1, fy + dy * 0.3 + ny * b1);
+        let c2 = (fx + dx * 0.7 + nx * b2, fy + dy * 0.7 + ny * b2);
+
+        // movement time depends on the distance and random speed:
+        let (min_speed, max_speed) = self.config.speed;
+        let speed = self.rng.range(min_speed, max_speed).max(1.0);
+        let step = self.config.step.max(Duration::from_millis(1));
+        let total = Duration::from_secs_f64(distance / speed + 0.08);
+        let steps = ((total.as_millis() / step.as_millis()) as usize).max(2);
+
+        let mut points = Vec::with_capacity(steps);
+        for i in 1..=steps {
+            // ease-in-out gives a variable speed (slow start & finish):
+            let t = i as f64 / steps as f64;
+            let t = t * t * (3.0 - 2.0 * t);
+            let u = 1.0 - t;
+
+            let mut px = u*u*u*fx + 3.0*u*u*t*c1.0 + 3.0*u*t*t*c2.0 + t*t*t*x;
+            let mut py = u*u*u*fy + 3.0*u*u*t*c1.1 + 3.0*u*t*t*c2.1 + t*t*t*y;
+
+            // the last point must hit the target exactly:
+            if i < steps {
+                px += self.rng.range(-self.config.jitter, self.config.jitter);
+                py += self.rng.range(-self.config.jitter, self.config.jitter);
+            }
+
+            let (px, py) = self.bound((px, py), (fx, fy), (x, y));
+            points.push((px.round() as i64, py.round() as i64, step));
+        }
+
+        points
+    }
+
+    /// Returns a neighbour key on the QWERTY layout (for typos)
+    fn neighbour(&mut self, c: char) -> Option<char> {
+        const ROWS: [&str; 3] = ["qwertyuiop", "asdfghjkl", "zxcvbnm"];
+
+        let lower = c.to_ascii_lowercase();
+        for row in ROWS {
+            let chars = row.chars().collect::<Vec<_>>();
+            if let Some(i) = chars.iter().position(|&k| k == lower) {
+                let j = match i {
+                    0 => 1,
+                    _ if i + 1 == chars.len() => i - 1,
+                    _ if self.rng.chance(0.5) => i - 1,
+                    _ => i + 1,
+                };
+
+                let typo = chars[j];
+                return Some(if c.is_ascii_uppercase() { typo.to_ascii_uppercase() } else { typo });
+            }
+        }
+
+        None
+    }
+}
+
+impl Actions<'_> {
+    /// Moves the mouse pointer to the viewport coordinates along a human-like trajectory
+    pub fn human_move_to(mut self, human: &mut Humanizer, x: i64, y: i64) -> Self {
+        for (px, py, step) in human.path(x as f64, y as f64) {
+            self = self.pointer_move(Origin::Viewport, px, py, step);
+        }
+        self
+    }
+
+    /// Pauses for a random dwell time
+    pub fn human_dwell(self, human: &mut Humanizer) -> Self {
+        let dwell = human.dwell();
+        self.pause(dwell)
+    }
+
+    /// Clicks the mouse button with random dwell times
+    pub fn human_click_button(self, human: &mut Humanizer, button: MouseButton) -> Self {
+        let hold = human.dwell();
+
+        self.human_dwell(human)
+            .pointer_down(button)
+            .pause(hold)
+            .pointer_up(button)
+    }
+
+    /// Clicks the left mouse button with random dwell times
+    pub fn human_click(self, human: &mut Humanizer) -> Self {
+        self.human_click_button(human, MouseButton::Left)
+    }
+
+    /// Moves to the viewport coordinates and clicks there like a human
+    pub fn human_click_at(self, human: &mut Humanizer, x: i64, y: i64) -> Self {
+        self.human_move_to(human, x, y).human_click(human)
+    }
+
+    /// Types the text with random delays and occasional corrected typos
+    pub fn human_type(mut self, human: &mut Humanizer, text: &str) -> Self {
+        for c in text.chars() {
+            // make a typo and erase it:
+            let typo_chance = human.config().typo_chance;
+            if human.rng().chance(typo_chance) && let Some(typo) = human.neighbour(c) {
+                let (delay, notice) = (human.key_delay(), human.dwell());
+                self = self.press(typo)
+                    .pause(delay + notice)
+                    .press(Key::Backspace);
+            }
+
+            let delay = human.key_delay();
+            self = self.pause(delay).press(c);
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_gives_same_path() {
+        let mut a = Humanizer::new(42);
+        let mut b = Humanizer::new(42);
+
+        for (x, y) in [(300.0, 200.0), (20.0, 700.0), (1000.0, 5.0)] {
+            assert_eq!(a.path(x, y), b.path(x, y));
+        }
+        assert_ne!(Humanizer::new(1).path(300.0, 200.0), Humanizer::new(2).path(300.0, 200.0));
+    }
+
+    #[test]
+    fn path_stays_within_move_box_without_viewport() {
+        let config = HumanConfig { curvature: 1.0, jitter: 5.0, overshoot_chance: 1.0, ..Default::default() };
+
+        for seed in 0..50 {
+            let mut human = Humanizer::with_config(seed, config.clone());
+            human.set_position(790.0, 5.0);
+
+            let mut from = (790, 5);
+            for (x, y) in [(2, 598), (799, 0), (0, 0), (300, 300)] {
+                let path = human.path(x as f64, y as f64);
+                assert!(path.iter().all(|&(px, py, _)| {
+                    (from.0.min(x)..=from.0.max(x)).contains(&px) && (from.1.min(y)..=from.1.max(y)).contains(&py)
+                }));
+                assert_eq!(path.last().map(|&(px, py, _)| (px, py)), Some((x, y)));
+                from = (x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn path_stays_within_viewport() {
+        let config = HumanConfig { curvature: 1.0, jitter: 5.0, overshoot_chance: 1.0, ..Default::default() };
+
+        for seed in 0..50 {
+            let mut human = Humanizer::with_config(seed, config.clone()).viewport(800, 600);
+            human.set_position(790.0, 5.0);
+
+            for (x, y) in [(2.0, 598.0), (799.0, 0.0), (0.0, 0.0), (2000.0, -50.0)] {
+                let path = human.path(x, y);
+                assert!(path.iter().all(|&(px, py, _)| (0..800).contains(&px) && (0..600).contains(&py)));
+            }
+        }
+    }
+}
@@ -0,0 +1,195 @@
+/// The keyboard key (WebDriver special keys are mapped to their codepoints)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Key {
+    /// A regular printable character
+    Char(char),
+
+    Null,
+    Cancel,
+    Help,
+    Backspace,
+    Tab,
+    Clear,
+    Return,
+    Enter,
+    Shift,
+    Control,
+    Alt,
+    Pause,
+    Escape,
+    Space,
+    PageUp,
+    PageDown,
+    End,
+    Home,
+    ArrowLeft,
+    ArrowUp,
+    ArrowRight,
+    ArrowDown,
+    Insert,
+    Delete,
+    Semicolon,
+    Equals,
+
+    Numpad0,
+    Numpad1,
+    Numpad2,
+    Numpad3,
+    Numpad4,
+    Numpad5,
+    Numpad6,
+    Numpad7,
+    Numpad8,
+    Numpad9,
+    Multiply,
+    Add,
+    Separator,
+    Subtract,
+    Decimal,
+    Divide,
+
+    F1,
+    F2,
+    F3,
+    F4,
+    F5,
+    F6,
+    F7,
+    F8,
+    F9,
+    F10,
+    F11,
+    F12,
+
+    Meta,
+    ZenkakuHankaku,
+
+    RightShift,
+    RightControl,
+    RightAlt,
+    RightMeta,
+
+    NumpadPageUp,
+    NumpadPageDown,
+    NumpadEnd,
+    NumpadHome,
+    NumpadArrowLeft,
+    NumpadArrowUp,
+    NumpadArrowRight,
+    NumpadArrowDown,
+    NumpadInsert,
+    NumpadDelete,
+}
+
+impl Key {
+    /// Returns the key codepoint used by WebDriver
+    pub fn codepoint(&self) -> char {
+        match self {
+            Self::Char(c) => *c,
+
+            Self::Null => '\u{E000}',
+            Self::Cancel => '\u{E001}',
+            Self::Help => '\u{E002}',
+            Self::Backspace => '\u{E003}',
+            Self::Tab => '\u{E004}',
+            Self::Clear => '\u{E005}',
+            Self::Return => '\u{E006}',
+            Self::Enter => '\u{E007}',
+            Self::Shift => '\u{E008}',
+            Self::Control => '\u{E009}',
+            Self::Alt => '\u{E00A}',
+            Self::Pause => '\u{E00B}',
+            Self::Escape => '\u{E00C}',
+            Self::Space => '\u{E00D}',
+            Self::PageUp => '\u{E00E}',
+            Self::PageDown => '\u{E00F}',
+            Self::End => '\u{E010}',
+            Self::Home => '\u{E011}',
+            Self::ArrowLeft => '\u{E012}',
+            Self::ArrowUp => '\u{E013}',
+            Self::ArrowRight => '\u{E014}',
+            Self::ArrowDown => '\u{E015}',
+            Self::Insert => '\u{E016}',
+            Self::Delete => '\u{E017}',
+            Self::Semicolon => '\u{E018}',
+            Self::Equals => '\u{E019}',
+
+            Self::Numpad0 => '\u{E01A}',
+            Self::Numpad1 => '\u{E01B}',
+            Self::Numpad2 => '\u{E01C}',
+            Self::Numpad3 => '\u{E01D}',
+            Self::Numpad4 => '\u{E01E}',
+            Self::Numpad5 => '\u{E01F}',
+            Self::Numpad6 => '\u{E020}',
+            Self::Numpad7 => '\u{E021}',
+            Self::Numpad8 => '\u{E022}',
+            Self::Numpad9 => '\u{E023}',
+            Self::Multiply => '\u{E024}',
+            Self::Add => '\u{E025}',
+            Self::Separator => '\u{E026}',
+            Self::Subtract => '\u{E027}',
+            Self::Decimal => '\u{E028}',
+            Self::Divide => '\u{E029}',
+
+            Self::F1 => '\u{E031}',
+            Self::F2 => '\u{E032}',
+            Self::F3 => '\u{E033}',
+            Self::F4 => '\u{E034}',
+            Self::F5 => '\u{E035}',
+            Self::F6 => '\u{E036}',
+            Self::F7 => '\u{E037}',
+            Self::F8 => '\u{E038}',
+            Self::F9 => '\u{E039}',
+            Self::F10 => '\u{E03A}',
+            Self::F11 => '\u{E03B}',
+            Self::F12 => '\u{E03C}',
+
+            Self::Meta => '\u{E03D}',
+            Self::ZenkakuHankaku => '\u{E040}',
+
+            Self::RightShift => '\u{E050}',
+            Self::RightControl => '\u{E051}',
+            Self::RightAlt => '\u{E052}',
+            Self::RightMeta => '\u{E053}',
+
+            Self::NumpadPageUp => '\u{E054}',
+            Self::NumpadPageDown => '\u{E055}',
+            Self::NumpadEnd => '\u{E056}',
+            Self::NumpadHome => '\u{E057}',
+            Self::NumpadArrowLeft => '\u{E058}',
+            Self::NumpadArrowUp => '\u{E059}',
+            Self::NumpadArrowRight => '\u{E05A}',
+            Self::NumpadArrowDown => '\u{E05B}',
+            Self::NumpadInsert => '\u{E05C}',
+            Self::NumpadDelete => '\u{E05D}',
+        }
+    }
+}
+
+impl From<char> for Key {
+    fn from(c: char) -> Self {
+        Self::Char(c)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_keys_to_webdriver_codepoints() {
+        assert_eq!(Key::from('a').codepoint(), 'a');
+        assert_eq!(Key::Null.codepoint(), '\u{E000}');
+        assert_eq!(Key::Enter.codepoint(), '\u{E007}');
+        assert_eq!(Key::Shift.codepoint(), '\u{E008}');
+        assert_eq!(Key::Equals.codepoint(), '\u{E019}');
+        assert_eq!(Key::Numpad0.codepoint(), '\u{E01A}');
+        assert_eq!(Key::Divide.codepoint(), '\u{E029}');
+        assert_eq!(Key::F1.codepoint(), '\u{E031}');
+        assert_eq!(Key::F12.codepoint(), '\u{E03C}');
+        assert_eq!(Key::Meta.codepoint(), '\u{E03D}');
+        assert_eq!(Key::ZenkakuHankaku.codepoint(), '\u{E040}');
+        assert_eq!(Key::RightShift.codepoint(), '\u{E050}');
+        assert_eq!(Key::NumpadDelete.codepoint(), '\u{E05D}');
+    }
+}
@@ -0,0 +1,3 @@
+pub mod key;      pub use key::Key;
+pub mod actions;  pub use actions::{ Actions, MouseButton, Origin };
+pub mod human;    pub use human::{ Humanizer, HumanConfig, Rng };
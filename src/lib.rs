@@ -2,5 +2,14 @@
 
 pub mod error;   pub use error::{ Error, Result };
 pub mod prelude;
+pub(crate) mod ws;
 
-pub mod session;  pub use session::{ Session, Tab };
+pub mod session;  pub use session::{ Session, SessionBuilder, BrowserContext, ContextOptions, Tab, Storage, StorageSnapshot, Timeouts, PageLoadStrategy, WaitUntil };
+pub mod input;    pub use input::{ Actions, Key, Humanizer };
+pub mod vision;   pub use vision::{ Rect, ImageMatch };
+pub mod cdp;      pub use cdp::CdpSession;
+pub mod bidi;     pub use bidi::BiDi;
+pub mod console;  pub use console::{ ConsoleMessage, ConsoleLevel };
+pub mod permission;  pub use permission::{ Permission, PermissionState };
+pub mod emulation;  pub use emulation::{ UserAgent, Device, Geolocation, NetworkConditions };
+pub mod network;  pub use network::{ RouteAction, Fulfillment, Overrides, BlockRules, BlockStats, Har, ReplayOptions, CapturedResponse, PerformanceEvent, Proxy };
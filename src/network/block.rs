@@ -0,0 +1,68 @@
+use crate::cdp::Network::ResourceType;
+
+use std::collections::HashMap;
+
+/// The request blocking rules (resource types & URL glob patterns)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BlockRules {
+    pub resource_types: Vec<ResourceType>,
+    pub patterns: Vec<String>,
+}
+
+impl BlockRules {
+    /// Creates empty rules
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Blocks images, fonts and media (the common scraping setup)
+    pub fn heavy() -> Self {
+        Self::new().resource_types([ResourceType::Image, ResourceType::Font, ResourceType::Media])
+    }
+
+    /// Blocks the resource type (e.g. 'ResourceType::Image')
+    pub fn resource_type(mut self, resource_type: ResourceType) -> Self {
+        if !self.resource_types.contains(&resource_type) {
+            self.resource_types.push(resource_type);
+        }
+        self
+    }
+
+    /// Blocks the resource types
+    pub fn resource_types(self, resource_types: impl IntoIterator<Item = ResourceType>) -> Self {
+        resource_types.into_iter().fold(self, |rules, t| rules.resource_type(t))
+    }
+
+    /// Blocks the URLs matching the glob pattern (e.g. '*://*.doubleclick.net/*')
+    pub fn pattern<S: Into<String>>(mut self, pattern: S) -> Self {
+        self.patterns.push(pattern.into());
+        self
+    }
+
+    /// Returns true if there are no rules
+    pub fn is_empty(&self) -> bool {
+        self.resource_types.is_empty() && self.patterns.is_empty()
+    }
+}
+
+/// The blocked requests statistics of the tab
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BlockStats {
+    /// The total count of blocked requests
+    pub total: u64,
+    /// The blocked requests count by resource type
+    pub by_type: HashMap<ResourceType, u64>,
+}
+
+impl BlockStats {
+    /// Counts the blocked request
+    pub(crate) fn count(&mut self, resource_type: ResourceType) {
+        self.total += 1;
+        *self.by_type.entry(resource_type).or_default() += 1;
+    }
+
+    /// Returns the blocked requests count of the resource type
+    pub fn get(&self, resource_type: ResourceType) -> u64 {
+        self.by_type.get(&resource_type).copied().unwrap_or(0)
+    }
+}
@@ -0,0 +1,255 @@
+use crate::prelude::*;
+
+use serde::{ Serialize, Deserialize };
+use serde_json::Value;
+
+/// The HTTP Archive (HAR 1.2)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Har {
+    pub log: HarLog,
+}
+
+impl Har {
+    /// Creates an empty archive
+    pub fn new() -> Self {
+        Self {
+            log: HarLog {
+                version: str!("1.2"),
+                creator: HarCreator {
+                    name: str!(env!("CARGO_PKG_NAME")),
+                    version: str!(env!("CARGO_PKG_VERSION")),
+                },
+                entries: vec![],
+                comment: None,
+            },
+        }
+    }
+
+    /// Parses the archive from JSON
+    pub fn from_json(json: &str) -> Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Serializes the archive to JSON
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Loads the archive from the file
+    pub async fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_json(&tokio::fs::read_to_string(path).await?)
+    }
+
+    /// Saves the archive to the file
+    pub async fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        tokio::fs::write(path, self.to_json()?).await?;
+        Ok(())
+    }
+}
+
+/// The archive root
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HarLog {
+    pub version: String,
+    pub creator: HarCreator,
+    pub entries: Vec<HarEntry>,
+    /// The recording notes (e.g. the lost network events)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+}
+
+/// The archive creator application
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HarCreator {
+    pub name: String,
+    pub version: String,
+}
+
+/// The recorded request & response
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct HarEntry {
+    /// The request start time (ISO 8601)
+    pub started_date_time: String,
+    /// The total request time (ms)
+    pub time: f64,
+    pub request: HarRequest,
+    pub response: HarResponse,
+    pub cache: HarCache,
+    pub timings: HarTimings,
+    #[serde(rename = "serverIPAddress", skip_serializing_if = "Option::is_none")]
+    pub server_ip_address: Option<String>,
+    /// The resource type (the DevTools extension field)
+    #[serde(rename = "_resourceType", skip_serializing_if = "Option::is_none")]
+    pub resource_type: Option<String>,
+}
+
+/// The recorded request
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct HarRequest {
+    pub method: String,
+    pub url: String,
+    pub http_version: String,
+    pub cookies: Vec<HarPair>,
+    pub headers: Vec<HarPair>,
+    pub query_string: Vec<HarPair>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub post_data: Option<HarPostData>,
+    pub headers_size: i64,
+    pub body_size: i64,
+}
+
+/// The recorded response
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct HarResponse {
+    pub status: i64,
+    pub status_text: String,
+    pub http_version: String,
+    pub cookies: Vec<HarPair>,
+    pub headers: Vec<HarPair>,
+    pub content: HarContent,
+    #[serde(rename = "redirectURL")]
+    pub redirect_url: String,
+    pub headers_size: i64,
+    pub body_size: i64,
+}
+
+/// The name & value pair (header, cookie or query parameter)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HarPair {
+    pub name: String,
+    pub value: String,
+}
+
+/// The request body
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct HarPostData {
+    pub mime_type: String,
+    pub text: String,
+}
+
+/// The response body
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct HarContent {
+    pub size: i64,
+    pub mime_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    /// The text encoding (e.g. 'base64' for binary bodies)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<String>,
+}
+
+impl HarContent {
+    /// Returns the decoded body bytes (None = the body was not recorded)
+    pub fn bytes(&self) -> Option<Vec<u8>> {
+        use base64::{ Engine, engine::general_purpose::STANDARD as BASE64 };
+
+        let text = self.text.as_ref()?;
+        match self.encoding.as_deref() {
+            Some("base64") => BASE64.decode(text).ok(),
+            _ => Some(text.as_bytes().to_vec()),
+        }
+    }
+}
+
+/// The cache usage info (not recorded)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HarCache {}
+
+/// The request phases timings (ms, -1 = not applicable)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HarTimings {
+    pub blocked: f64,
+    pub dns: f64,
+    pub connect: f64,
+    pub send: f64,
+    pub wait: f64,
+    pub receive: f64,
+    pub ssl: f64,
+}
+
+impl HarPair {
+    /// Converts the CDP headers object to pairs (multiple values are separated by '\n')
+    pub(crate) fn from_headers(headers: &Value) -> Vec<Self> {
+        let Some(headers) = headers.as_object() else { return vec![] };
+
+        headers.iter()
+            .flat_map(|(name, value)| {
+                value.as_str()
+                    .unwrap_or_default()
+                    .split('\n')
+                    .map(|value| Self { name: name.clone(), value: value.to_owned() })
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    /// Parses the URL query string
+    pub(crate) fn from_query(url: &str) -> Vec<Self> {
+        let url = url.split('#').next().unwrap_or_default();
+        let Some((_, query)) = url.split_once('?') else { return vec![] };
+
+        query.split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+                Self { name: name.to_owned(), value: value.to_owned() }
+            })
+            .collect()
+    }
+}
+
+/// Formats the UNIX time (seconds) as ISO 8601 date (UTC)
+pub(crate) fn iso_date(secs: f64) -> String {
+    let ms = (secs * 1000.0).round() as i64;
+    let (days, ms) = (ms.div_euclid(86_400_000), ms.rem_euclid(86_400_000));
+
+    // the civil date from days count:
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + (month <= 2) as i64;
+
+    fmt!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:03}Z",
+        ms / 3_600_000, ms / 60_000 % 60, ms / 1000 % 60, ms % 1000
+    )
+}
+
+/// Converts the CDP protocol name to the HTTP version (e.g. 'h2' -> 'HTTP/2')
+pub(crate) fn http_version(protocol: &str) -> String {
+    match protocol {
+        "h2" => str!("HTTP/2"),
+        "h3" | "h3-29" => str!("HTTP/3"),
+        "" => str!("HTTP/1.1"),
+        other => other.to_uppercase(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_iso_dates() {
+        assert_eq!(iso_date(0.0), "1970-01-01T00:00:00.000Z");
+        assert_eq!(iso_date(951782400.0), "2000-02-29T00:00:00.000Z");
+        assert_eq!(iso_date(1700000000.5), "2023-11-14T22:13:20.500Z");
+        assert_eq!(iso_date(-1.0), "1969-12-31T23:59:59.000Z");
+    }
+}
@@ -0,0 +1,35 @@
+pub mod block;   pub use block::{ BlockRules, BlockStats };
+pub mod router;  pub use router::{ Fulfillment, Overrides, RouteAction, RouteHandler };
+pub(crate) use router::{ Router, RouteKind };
+pub mod har;     pub use har::{ Har, HarEntry, HarRequest, HarResponse, HarContent, HarTimings, HarPair };
+pub mod replay;  pub use replay::{ ReplayOptions, Unmatched };
+pub mod response;  pub use response::CapturedResponse;
+pub mod performance;  pub use performance::{ PerformanceEntry, PerformanceEvent };
+pub mod proxy;   pub use proxy::{ Proxy, ProxyKind, ProxyAuth, ProxyCredentials };
+pub(crate) mod recorder;  pub(crate) use recorder::HarRecorder;
+
+/// Checks the URL against the glob pattern ('*' matches any chars, '?' matches a single char)
+pub fn glob_match(pattern: &str, url: &str) -> bool {
+    let (pattern, url) = (pattern.as_bytes(), url.as_bytes());
+    let (mut p, mut u) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+
+    while u < url.len() {
+        if p < pattern.len() && (pattern[p] == b'?' || pattern[p] == url[u]) {
+            p += 1;
+            u += 1;
+        } else if p < pattern.len() && pattern[p] == b'*' {
+            star = Some((p, u));
+            p += 1;
+        } else if let Some((sp, su)) = star {
+            // backtrack: let the last '*' consume one more char
+            p = sp + 1;
+            u = su + 1;
+            star = Some((sp, su + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == b'*')
+}
@@ -0,0 +1,120 @@
+use crate::cdp::{ Event, Network, Page, RawEvent };
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+/// The performance log event (the CDP event wrapped by chromedriver)
+#[derive(Debug, Clone, PartialEq)]
+pub enum PerformanceEvent {
+    RequestWillBeSent(Box<Network::RequestWillBeSent>),
+    ResponseReceived(Box<Network::ResponseReceived>),
+    DataReceived(Network::DataReceived),
+    LoadingFinished(Network::LoadingFinished),
+    LoadingFailed(Network::LoadingFailed),
+    FrameNavigated(Box<Page::FrameNavigated>),
+    DomContentEventFired(Page::DomContentEventFired),
+    LoadEventFired(Page::LoadEventFired),
+    /// Other event (or the event not matching the vendored protocol version)
+    Other(RawEvent),
+}
+
+impl PerformanceEvent {
+    /// Parses the CDP event by its method name
+    pub fn parse(method: &str, params: Value) -> Self {
+        fn typed<E: Event + DeserializeOwned>(params: &Value) -> Option<E> {
+            serde_json::from_value(params.clone()).ok()
+        }
+
+        let event = match method {
+            Network::RequestWillBeSent::METHOD => typed(&params).map(|e| Self::RequestWillBeSent(Box::new(e))),
+            Network::ResponseReceived::METHOD => typed(&params).map(|e| Self::ResponseReceived(Box::new(e))),
+            Network::DataReceived::METHOD => typed(&params).map(Self::DataReceived),
+            Network::LoadingFinished::METHOD => typed(&params).map(Self::LoadingFinished),
+            Network::LoadingFailed::METHOD => typed(&params).map(Self::LoadingFailed),
+            Page::FrameNavigated::METHOD => typed(&params).map(|e| Self::FrameNavigated(Box::new(e))),
+            Page::DomContentEventFired::METHOD => typed(&params).map(Self::DomContentEventFired),
+            Page::LoadEventFired::METHOD => typed(&params).map(Self::LoadEventFired),
+            _ => None,
+        };
+
+        event.unwrap_or_else(|| Self::Other(RawEvent { method: method.to_owned(), params }))
+    }
+
+    /// Returns the CDP method name (e.g. 'Network.responseReceived')
+    pub fn method(&self) -> &str {
+        match self {
+            Self::RequestWillBeSent(_) => Network::RequestWillBeSent::METHOD,
+            Self::ResponseReceived(_) => Network::ResponseReceived::METHOD,
+            Self::DataReceived(_) => Network::DataReceived::METHOD,
+            Self::LoadingFinished(_) => Network::LoadingFinished::METHOD,
+            Self::LoadingFailed(_) => Network::LoadingFailed::METHOD,
+            Self::FrameNavigated(_) => Page::FrameNavigated::METHOD,
+            Self::DomContentEventFired(_) => Page::DomContentEventFired::METHOD,
+            Self::LoadEventFired(_) => Page::LoadEventFired::METHOD,
+            Self::Other(event) => &event.method,
+        }
+    }
+}
+
+/// The performance log entry
+#[derive(Debug, Clone, PartialEq)]
+pub struct PerformanceEntry {
+    /// The log time (UNIX ms)
+    pub timestamp: u64,
+    pub event: PerformanceEvent,
+}
+
+/// Parses the '/se/log' performance entries and returns them by tab (target id, entry)
+pub(crate) fn parse_log(log: &Value) -> Vec<(String, PerformanceEntry)> {
+    let Some(log) = log.as_array() else { return vec![] };
+
+    log.iter()
+        .filter_map(|entry| {
+            // the message is the JSON string '{"message": {"method", "params"}, "webview"}':
+            let message = serde_json::from_str::<Value>(entry["message"].as_str()?).ok()?;
+
+            // the entries without the tab (e.g. the browser target events) are skipped:
+            let target_id = message["webview"].as_str()?.to_owned();
+            let message = &message["message"];
+
+            let method = message["method"].as_str()?;
+            let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+            Some((target_id, PerformanceEntry {
+                timestamp: entry["timestamp"].as_u64().unwrap_or_default(),
+                event: PerformanceEvent::parse(method, params),
+            }))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parses_se_log_entries_by_tab() {
+        let log = json!([
+            {
+                "level": "INFO",
+                "timestamp": 1700000000123u64,
+                "message": "{\"message\":{\"method\":\"Page.loadEventFired\",\"params\":{\"timestamp\":12345.5}},\"webview\":\"8C3A2B1D\"}"
+            },
+            {
+                "level": "INFO",
+                "timestamp": 1700000000456u64,
+                "message": "{\"message\":{\"method\":\"Target.targetCreated\",\"params\":{}}}"
+            }
+        ]);
+
+        let entries = parse_log(&log);
+        assert_eq!(entries.len(), 1);
+
+        let (target_id, entry) = &entries[0];
+        assert_eq!(target_id, "8C3A2B1D");
+        assert_eq!(entry.timestamp, 1700000000123);
+        assert_eq!(entry.event.method(), "Page.loadEventFired");
+        assert!(matches!(entry.event, PerformanceEvent::LoadEventFired(_)));
+    }
+}
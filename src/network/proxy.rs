@@ -0,0 +1,178 @@
+use crate::prelude::*;
+
+use serde_json::{ json, Value };
+
+/// The proxy server mode
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProxyKind {
+    /// No proxy
+    Direct,
+    /// The OS proxy settings
+    System,
+    /// The proxy auto-detection (WPAD)
+    AutoDetect,
+    /// The proxy auto-config script URL
+    Pac(String),
+    /// The manual proxy servers ('host:port')
+    Manual {
+        http: Option<String>,
+        ssl: Option<String>,
+        socks: Option<String>,
+        socks_version: Option<u8>,
+    },
+    /// The chrome '--proxy-server' argument value (e.g. 'socks5://host:1080' or 'http=host:80;https=host:443')
+    Server(String),
+}
+
+/// The proxy authentication method
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ProxyAuth {
+    /// Answers the CDP 'Fetch.authRequired' (the tabs opened by 'Session::open' and the first tab)
+    #[default]
+    Fetch,
+    /// Loads the generated unpacked extension from a private temporary directory (all tabs, needs the extensions support),
+    /// the directory is removed when the session is closed or dropped,
+    /// note: the '--load-extension' argument is ignored by the old headless mode and the recent branded Chrome builds
+    /// (the auth then silently doesn't happen, use Chromium or Chrome for Testing), the headless sessions are refused
+    Extension,
+}
+
+/// The proxy credentials
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProxyCredentials {
+    pub username: String,
+    pub password: String,
+}
+
+/// The session proxy settings
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Proxy {
+    pub kind: ProxyKind,
+    /// The hosts bypassing the proxy (e.g. 'localhost', '*.internal')
+    pub no_proxy: Vec<String>,
+    pub credentials: Option<ProxyCredentials>,
+    pub auth: ProxyAuth,
+}
+
+impl Proxy {
+    /// Creates the proxy settings
+    pub fn new(kind: ProxyKind) -> Self {
+        Self {
+            kind,
+            no_proxy: vec![],
+            credentials: None,
+            auth: ProxyAuth::default(),
+        }
+    }
+
+    /// Uses no proxy
+    pub fn direct() -> Self {
+        Self::new(ProxyKind::Direct)
+    }
+
+    /// Uses the OS proxy settings
+    pub fn system() -> Self {
+        Self::new(ProxyKind::System)
+    }
+
+    /// Uses the proxy auto-config script
+    pub fn pac<S: Into<String>>(url: S) -> Self {
+        Self::new(ProxyKind::Pac(url.into()))
+    }
+
+    /// Uses the HTTP proxy for HTTP & HTTPS ('host:port')
+    pub fn http<S: Into<String>>(address: S) -> Self {
+        let address = address.into();
+        Self::new(ProxyKind::Manual { http: Some(address.clone()), ssl: Some(address), socks: None, socks_version: None })
+    }
+
+    /// Uses the SOCKS5 proxy ('host:port')
+    pub fn socks5<S: Into<String>>(address: S) -> Self {
+        Self::new(ProxyKind::Manual { http: None, ssl: None, socks: Some(address.into()), socks_version: Some(5) })
+    }
+
+    /// Uses the chrome '--proxy-server' argument
+    pub fn server<S: Into<String>>(value: S) -> Self {
+        Self::new(ProxyKind::Server(value.into()))
+    }
+
+    /// Appends the host bypassing the proxy
+    pub fn no_proxy<S: Into<String>>(mut self, host: S) -> Self {
+        self.no_proxy.push(host.into());
+        self
+    }
+
+    /// Sets the proxy credentials
+    pub fn credentials<U: Into<String>, P: Into<String>>(mut self, username: U, password: P) -> Self {
+        self.credentials = Some(ProxyCredentials { username: username.into(), password: password.into() });
+        self
+    }
+
+    /// Sets the proxy authentication method
+    pub fn auth(mut self, auth: ProxyAuth) -> Self {
+        self.auth = auth;
+        self
+    }
+
+    /// Returns the W3C 'proxy' capability (None for the '--proxy-server' argument)
+    pub(crate) fn to_capability(&self) -> Option<Value> {
+        let mut proxy = match &self.kind {
+            ProxyKind::Direct => json!({ "proxyType": "direct" }),
+            ProxyKind::System => json!({ "proxyType": "system" }),
+            ProxyKind::AutoDetect => json!({ "proxyType": "autodetect" }),
+            ProxyKind::Pac(url) => json!({ "proxyType": "pac", "proxyAutoconfigUrl": url }),
+            ProxyKind::Manual { http, ssl, socks, socks_version } => {
+                let mut proxy = json!({ "proxyType": "manual" });
+                if let Some(http) = http { proxy["httpProxy"] = json!(http); }
+                if let Some(ssl) = ssl { proxy["sslProxy"] = json!(ssl); }
+                if let Some(socks) = socks {
+                    proxy["socksProxy"] = json!(socks);
+                    proxy["socksVersion"] = json!(socks_version.unwrap_or(5));
+                }
+                proxy
+            },
+            ProxyKind::Server(_) => return None,
+        };
+
+        if !self.no_proxy.is_empty() && matches!(self.kind, ProxyKind::Manual { .. }) {
+            proxy["noProxy"] = json!(self.no_proxy);
+        }
+
+        Some(proxy)
+    }
+
+    /// Returns the chrome arguments (for the '--proxy-server' mode)
+    pub(crate) fn to_args(&self) -> Vec<String> {
+        let ProxyKind::Server(server) = &self.kind else { return vec![] };
+
+        let mut args = vec![fmt!("--proxy-server={server}")];
+        if !self.no_proxy.is_empty() {
+            args.push(fmt!("--proxy-bypass-list={}", self.no_proxy.join(";")));
+        }
+
+        args
+    }
+
+    /// Writes the proxy authentication extension into the existing (private) directory
+    pub(crate) async fn write_extension(&self, dir: &Path) -> Result<()> {
+        let Some(credentials) = &self.credentials else { return Ok(()) };
+
+        let manifest = json!({
+            "manifest_version": 3,
+            "name": "Proxy Authentication",
+            "version": "1.0",
+            "permissions": ["webRequest", "webRequestAuthProvider"],
+            "host_permissions": ["<all_urls>"],
+            "background": { "service_worker": "background.js" },
+        });
+        let script = fmt!(
+            "chrome.webRequest.onAuthRequired.addListener(\n    (details) => details.isProxy ? {{ authCredentials: {{ username: {}, password: {} }} }} : {{}},\n    {{ urls: ['<all_urls>'] }},\n    ['blocking']\n);\n",
+            json!(credentials.username), json!(credentials.password)
+        );
+
+        tokio::fs::write(dir.join("manifest.json"), serde_json::to_string_pretty(&manifest)?).await?;
+        tokio::fs::write(dir.join("background.js"), script).await?;
+
+        Ok(())
+    }
+}
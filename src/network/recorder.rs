@@ -0,0 +1,376 @@
+use crate::prelude::*;
+use crate::cdp::CdpSession;
+use super::har::{ self, * };
+
+use std::collections::HashMap;
+use futures::StreamExt;
+use serde_json::{ json, Value };
+use tokio::sync::oneshot;
+use tokio::task::{ JoinHandle, JoinSet };
+
+/// The request waiting for its response
+struct Pending {
+    /// The wall time of request start (seconds)
+    wall_time: f64,
+    /// The monotonic time of request start (seconds)
+    timestamp: f64,
+    request: Value,
+    response: Option<Value>,
+    resource_type: Option<String>,
+}
+
+/// The recording state
+#[derive(Default)]
+struct State {
+    pending: HashMap<String, Pending>,
+    entries: Vec<HarEntry>,
+    /// The number of the lost events (the recorder lagged behind)
+    lost: u64,
+}
+
+/// The tab network traffic recorder (works on the raw 'Network' events to not depend on the protocol version)
+pub(crate) struct HarRecorder {
+    state: Arc<std::sync::Mutex<State>>,
+    stop: Option<oneshot::Sender<()>>,
+    task: JoinHandle<()>,
+}
+
+impl HarRecorder {
+    /// Starts recording the tab network traffic
+    /// * with_bodies: fetch the response bodies (slower, the bodies are kept in memory)
+    pub(crate) async fn start(cdp: CdpSession, with_bodies: bool) -> Result<Self> {
+        let mut events = cdp.subscribe(&["Network"]).await?;
+
+        let state = Arc::new(std::sync::Mutex::new(State::default()));
+        let (stop, mut stopped) = oneshot::channel::<()>();
+        let task = {
+            let state = state.clone();
+
+            tokio::spawn(async move {
+                // the bodies are fetched aside to not hold up the events:
+                let mut bodies = JoinSet::new();
+
+                loop {
+                    let event = tokio::select! {
+                        event = events.next() => match event {
+                            Some(event) => event,
+                            None => break,
+                        },
+                        _ = &mut stopped => break,
+                    };
+                    while bodies.try_join_next().is_some() {}
+
+                    if let Some(missed) = event.lagged() {
+                        state.lock().unwrap().lost += missed;
+                        continue;
+                    }
+
+                    let params = &event.params;
+                    let request_id = params["requestId"].as_str().unwrap_or_default().to_owned();
+
+                    match event.method.as_str() {
+                        "Network.requestWillBeSent" => {
+                            let mut state = state.lock().unwrap();
+
+                            // redirect finishes the previous request:
+                            if let Some(response) = params.get("redirectResponse")
+                                && let Some(mut pending) = state.pending.remove(&request_id)
+                            {
+                                pending.response = Some(response.clone());
+                                let entry = entry(pending, params["timestamp"].as_f64(), None, None);
+                                state.entries.push(entry);
+                            }
+
+                            state.pending.insert(request_id, Pending {
+                                wall_time: params["wallTime"].as_f64().unwrap_or_default(),
+                                timestamp: params["timestamp"].as_f64().unwrap_or_default(),
+                                request: params["request"].clone(),
+                                response: None,
+                                resource_type: params["type"].as_str().map(|s| s.to_owned()),
+                            });
+                        },
+
+                        "Network.responseReceived" => {
+                            if let Some(pending) = state.lock().unwrap().pending.get_mut(&request_id) {
+                                pending.response = Some(params["response"].clone());
+                            }
+                        },
+
+                        "Network.loadingFinished" => {
+                            let Some(pending) = state.lock().unwrap().pending.remove(&request_id) else { continue };
+                            let (finished, encoded_length) = (params["timestamp"].as_f64(), params["encodedDataLength"].as_f64());
+
+                            if !with_bodies {
+                                state.lock().unwrap().entries.push(entry(pending, finished, encoded_length, None));
+                                continue;
+                            }
+
+                            let (cdp, state) = (cdp.clone(), state.clone());
+                            bodies.spawn(async move {
+                                let body = cdp.send("Network.getResponseBody", json!({ "requestId": request_id })).await.ok();
+                                state.lock().unwrap().entries.push(entry(pending, finished, encoded_length, body));
+                            });
+                        },
+
+                        "Network.loadingFailed" => {
+                            let mut state = state.lock().unwrap();
+                            if let Some(mut pending) = state.pending.remove(&request_id) {
+                                // the failed request has no response, so record the error instead:
+                                pending.response.get_or_insert_with(|| json!({
+                                    "status": 0,
+                                    "statusText": params["errorText"].as_str().unwrap_or_default(),
+                                }));
+
+                                let entry = entry(pending, params["timestamp"].as_f64(), None, None);
+                                state.entries.push(entry);
+                            }
+                        },
+
+                        _ => {}
+                    }
+                }
+
+                // the finished requests are recorded with their bodies:
+                bodies.join_all().await;
+            })
+        };
+
+        Ok(Self { state, stop: Some(stop), task })
+    }
+
+    /// Stops recording and returns the archive (unfinished requests with a response are included),
+    /// the bodies being fetched are waited for, the lost events are reported in the archive comment
+    pub(crate) async fn stop(mut self) -> Har {
+        if let Some(stop) = self.stop.take() {
+            let _ = stop.send(());
+        }
+        let _ = (&mut self.task).await;
+
+        let mut state = self.state.lock().unwrap();
+        let pending = state.pending.drain().map(|(_, p)| p).collect::<Vec<_>>();
+
+        let mut entries = std::mem::take(&mut state.entries);
+        entries.extend(pending.into_iter()
+            .filter(|p| p.response.is_some())
+            .map(|p| entry(p, None, None, None)));
+        entries.sort_by(|a, b| a.started_date_time.cmp(&b.started_date_time));
+
+        let mut har = Har::new();
+        har.log.entries = entries;
+        if state.lost > 0 {
+            har.log.comment = Some(fmt!("{} network events were lost, some requests may be missing", state.lost));
+        }
+        har
+    }
+}
+
+impl Drop for HarRecorder {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Builds the archive entry
+/// * finished: the monotonic time of loading end (seconds)
+/// * encoded_length: the transferred data size
+/// * body: the 'Network.getResponseBody' result
+fn entry(pending: Pending, finished: Option<f64>, encoded_length: Option<f64>, body: Option<Value>) -> HarEntry {
+    let request = &pending.request;
+    let response = pending.response.unwrap_or_default();
+    let version = har::http_version(response["protocol"].as_str().unwrap_or_default());
+    let server_ip_address = response["remoteIPAddress"].as_str().map(|s| s.to_owned());
+
+    // request:
+    let post_data = request["postData"].as_str().map(|text| HarPostData {
+        mime_type: header(&request["headers"], "content-type").unwrap_or_default(),
+        text: text.to_owned(),
+    });
+    let url = request["url"].as_str().unwrap_or_default().to_owned();
+
+    let request = HarRequest {
+        method: request["method"].as_str().unwrap_or("GET").to_owned(),
+        query_string: HarPair::from_query(&url),
+        url,
+        http_version: version.clone(),
+        cookies: vec![],
+        headers: HarPair::from_headers(&request["headers"]),
+        body_size: post_data.as_ref().map(|d| d.text.len() as i64).unwrap_or(0),
+        post_data,
+        headers_size: -1,
+    };
+
+    // response body:
+    let mut content = HarContent {
+        size: 0,
+        mime_type: response["mimeType"].as_str().unwrap_or_default().to_owned(),
+        text: None,
+        encoding: None,
+    };
+    if let Some(body) = body
+        && let Some(text) = body["body"].as_str()
+    {
+        let base64 = body["base64Encoded"].as_bool().unwrap_or(false);
+
+        content.size = if base64 { base64_size(text) as i64 } else { text.len() as i64 };
+        content.text = Some(text.to_owned());
+        content.encoding = base64.then(|| str!("base64"));
+    }
+
+    // timings:
+    let total = match finished {
+        Some(finished) => ((finished - pending.timestamp) * 1000.0).max(0.0),
+        None => 0.0,
+    };
+    let timings = timings(&response["timing"], finished, total);
+
+    let response = HarResponse {
+        status: response["status"].as_i64().unwrap_or_default(),
+        status_text: response["statusText"].as_str().unwrap_or_default().to_owned(),
+        http_version: version,
+        cookies: vec![],
+        headers: HarPair::from_headers(&response["headers"]),
+        redirect_url: header(&response["headers"], "location").unwrap_or_default(),
+        headers_size: -1,
+        body_size: encoded_length.map(|l| l as i64).unwrap_or(-1),
+        content,
+    };
+
+    HarEntry {
+        started_date_time: har::iso_date(pending.wall_time),
+        time: [timings.blocked, timings.dns, timings.connect, timings.send, timings.wait, timings.receive]
+            .iter()
+            .filter(|t| **t > 0.0)
+            .sum(),
+        request,
+        response,
+        cache: HarCache {},
+        timings,
+        server_ip_address,
+        resource_type: pending.resource_type,
+    }
+}
+
+/// Returns the decoded size of the base64 text (without decoding)
+fn base64_size(text: &str) -> usize {
+    let padding = text.bytes().rev().take_while(|b| *b == b'=').count();
+    (text.len() / 4 * 3).saturating_sub(padding)
+}
+
+/// Returns the header value (case-insensitive)
+fn header(headers: &Value, name: &str) -> Option<String> {
+    headers.as_object()?
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .and_then(|(_, value)| value.as_str())
+        .map(|value| value.to_owned())
+}
+
+/// Converts the CDP resource timing to the HAR timings
+/// * total: the whole request time (used if the detailed timing is unknown)
+fn timings(timing: &Value, finished: Option<f64>, total: f64) -> HarTimings {
+    let Some(request_time) = timing["requestTime"].as_f64() else {
+        return HarTimings { blocked: -1.0, dns: -1.0, connect: -1.0, send: 0.0, wait: total, receive: 0.0, ssl: -1.0 };
+    };
+    let at = |name: &str| timing[name].as_f64().unwrap_or(-1.0);
+    let span = |start: f64, end: f64| if start >= 0.0 && end >= start { end - start } else { -1.0 };
+
+    let (dns_start, connect_start, send_start) = (at("dnsStart"), at("connectStart"), at("sendStart"));
+    let blocked = [dns_start, connect_start, send_start]
+        .into_iter()
+        .find(|t| *t >= 0.0)
+        .unwrap_or(0.0);
+
+    let headers_end = at("receiveHeadersEnd");
+    let receive = match finished {
+        Some(finished) => ((finished - request_time) * 1000.0 - headers_end).max(0.0),
+        None => 0.0,
+    };
+
+    HarTimings {
+        blocked,
+        dns: span(dns_start, at("dnsEnd")),
+        connect: span(connect_start, at("connectEnd")),
+        send: span(send_start, at("sendEnd")).max(0.0),
+        wait: span(at("sendEnd"), headers_end).max(0.0),
+        receive,
+        ssl: span(at("sslStart"), at("sslEnd")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn approx(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-6
+    }
+
+    #[test]
+    fn counts_base64_size_without_padding() {
+        assert_eq!(base64_size(""), 0);
+        assert_eq!(base64_size("YWJj"), 3);
+        assert_eq!(base64_size("aGk="), 2);
+        assert_eq!(base64_size("aGVsbG8="), 5);
+        assert_eq!(base64_size("aA=="), 1);
+    }
+
+    #[test]
+    fn builds_entry_from_network_events() {
+        let pending = Pending {
+            wall_time: 1700000000.5,
+            timestamp: 100.0,
+            request: json!({
+                "url": "https://example.com/api?q=1&x=2",
+                "method": "POST",
+                "headers": { "Content-Type": "application/json" },
+                "postData": "{\"a\":1}",
+            }),
+            response: Some(json!({
+                "status": 302,
+                "statusText": "Found",
+                "protocol": "h2",
+                "mimeType": "application/json",
+                "headers": { "Location": "/next" },
+                "remoteIPAddress": "1.2.3.4",
+                "timing": {
+                    "requestTime": 100.0,
+                    "dnsStart": 1.0, "dnsEnd": 3.0,
+                    "connectStart": 3.0, "connectEnd": 8.0,
+                    "sslStart": 5.0, "sslEnd": 8.0,
+                    "sendStart": 8.5, "sendEnd": 9.0,
+                    "receiveHeadersEnd": 20.0,
+                },
+            })),
+            resource_type: Some(str!("Fetch")),
+        };
+
+        let entry = entry(pending, Some(100.05), Some(321.0), Some(json!({ "body": "aGVsbG8=", "base64Encoded": true })));
+
+        assert_eq!(entry.started_date_time, "2023-11-14T22:13:20.500Z");
+        assert_eq!(entry.request.method, "POST");
+        assert_eq!(entry.request.http_version, "HTTP/2");
+        assert_eq!(entry.request.query_string.len(), 2);
+        assert_eq!(entry.request.body_size, 7);
+        assert_eq!(entry.request.post_data.as_ref().map(|d| d.mime_type.as_str()), Some("application/json"));
+
+        assert_eq!(entry.response.status, 302);
+        assert_eq!(entry.response.redirect_url, "/next");
+        assert_eq!(entry.response.body_size, 321);
+        assert_eq!(entry.response.content.size, 5);
+        assert_eq!(entry.response.content.encoding.as_deref(), Some("base64"));
+        assert_eq!(entry.server_ip_address.as_deref(), Some("1.2.3.4"));
+
+        let t = &entry.timings;
+        assert!(approx(t.blocked, 1.0) && approx(t.dns, 2.0) && approx(t.connect, 5.0) && approx(t.ssl, 3.0));
+        assert!(approx(t.send, 0.5) && approx(t.wait, 11.0) && approx(t.receive, 30.0));
+        assert!(approx(entry.time, 49.5));
+    }
+
+    #[test]
+    fn uses_total_time_without_detailed_timing() {
+        let timings = timings(&Value::Null, Some(1.0), 12.0);
+
+        assert!(approx(timings.wait, 12.0));
+        assert!(approx(timings.blocked, -1.0) && approx(timings.dns, -1.0) && approx(timings.connect, -1.0));
+    }
+}
@@ -0,0 +1,238 @@
+use crate::prelude::*;
+use crate::cdp::{ Fetch, Network };
+use super::{ Har, HarEntry, Fulfillment, RouteAction, RouteHandler };
+
+use std::collections::HashMap;
+
+/// The response headers not valid for the recorded (already decoded) bodies
+const SKIP_HEADERS: &[&str] = &["content-encoding", "content-length", "transfer-encoding"];
+
+/// The policy for requests missing in the archive or recorded without the body (see 'Tab::start_har')
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Unmatched {
+    /// Fails the request (no network access at all)
+    #[default]
+    Abort,
+    /// Sends the request to the network
+    Continue,
+}
+
+/// The HAR replay options
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReplayOptions {
+    /// Compare the request bodies too
+    pub match_body: bool,
+    pub unmatched: Unmatched,
+}
+
+impl ReplayOptions {
+    /// Creates the default options (match by method & URL, abort the unmatched requests)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Compares the request bodies too
+    pub fn match_body(mut self, enable: bool) -> Self {
+        self.match_body = enable;
+        self
+    }
+
+    /// Sets the policy for requests missing in the archive or recorded without the body
+    pub fn unmatched(mut self, policy: Unmatched) -> Self {
+        self.unmatched = policy;
+        self
+    }
+}
+
+/// The request matching key (method, URL without fragment, body)
+type Key = (String, String, Option<String>);
+
+/// The archive responses served by the route
+struct Replay {
+    options: ReplayOptions,
+    /// The responses by request (repeated requests get the recorded responses in order)
+    responses: HashMap<Key, Vec<HarEntry>>,
+    /// The served responses count by request
+    served: std::sync::Mutex<HashMap<Key, usize>>,
+}
+
+impl Replay {
+    /// Builds the matching key
+    fn key(&self, method: &str, url: &str, body: Option<&str>) -> Key {
+        let url = url.split('#').next().unwrap_or_default();
+        let body = if self.options.match_body { body.map(|b| b.to_owned()) } else { None };
+
+        (method.to_uppercase(), url.to_owned(), body)
+    }
+
+    /// Answers the paused request
+    fn action(&self, event: &Fetch::RequestPaused) -> RouteAction {
+        let request = &event.request;
+        let key = self.key(&request.method, &request.url, request.post_data.as_deref());
+
+        let Some(entries) = self.responses.get(&key) else {
+            return self.unmatched();
+        };
+
+        // the last recorded response is repeated:
+        let index = {
+            let mut served = self.served.lock().unwrap();
+            let count = served.entry(key).or_default();
+            *count += 1;
+            (*count - 1).min(entries.len() - 1)
+        };
+        let response = &entries[index].response;
+
+        // the failed request was recorded:
+        if response.status == 0 {
+            return RouteAction::Abort(Network::ErrorReason::Failed);
+        }
+
+        // the body was not recorded (e.g. by 'Tab::start_har(false)'), so an empty body would be wrong:
+        let bodiless = request.method.eq_ignore_ascii_case("HEAD") || matches!(response.status, 100..=199 | 204 | 205 | 300..=399);
+        if response.content.text.is_none() && !bodiless {
+            return self.unmatched();
+        }
+
+        let mut fulfillment = Fulfillment::new(response.status as u16)
+            .body(response.content.bytes().unwrap_or_default());
+        for header in &response.headers {
+            if !SKIP_HEADERS.contains(&header.name.to_lowercase().as_str()) {
+                fulfillment = fulfillment.header(header.name.clone(), header.value.clone());
+            }
+        }
+
+        RouteAction::Fulfill(fulfillment)
+    }
+
+    /// Returns the action for the request that can't be answered from the archive
+    fn unmatched(&self) -> RouteAction {
+        match self.options.unmatched {
+            Unmatched::Abort => RouteAction::Abort(Network::ErrorReason::InternetDisconnected),
+            Unmatched::Continue => RouteAction::Continue,
+        }
+    }
+}
+
+/// Creates the route handler answering requests from the archive
+pub(crate) fn handler(har: &Har, options: ReplayOptions) -> RouteHandler {
+    let mut replay = Replay {
+        options,
+        responses: HashMap::new(),
+        served: std::sync::Mutex::new(HashMap::new()),
+    };
+
+    for entry in &har.log.entries {
+        let body = entry.request.post_data.as_ref().map(|d| d.text.as_str());
+        let key = replay.key(&entry.request.method, &entry.request.url, body);
+
+        replay.responses.entry(key).or_default().push(entry.clone());
+    }
+
+    Arc::new(move |event| replay.action(event))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::{ HarContent, HarRequest, HarResponse, har::HarPostData };
+    use serde_json::json;
+
+    fn entry(method: &str, url: &str, body: Option<&str>, status: i64, text: Option<&str>) -> HarEntry {
+        HarEntry {
+            request: HarRequest {
+                method: method.to_owned(),
+                url: url.to_owned(),
+                post_data: body.map(|text| HarPostData { mime_type: str!("text/plain"), text: text.to_owned() }),
+                ..Default::default()
+            },
+            response: HarResponse {
+                status,
+                content: HarContent { text: text.map(|t| t.to_owned()), ..Default::default() },
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    fn paused(method: &str, url: &str, body: Option<&str>) -> Fetch::RequestPaused {
+        serde_json::from_value(json!({
+            "requestId": "1",
+            "frameId": "F",
+            "resourceType": "Fetch",
+            "request": {
+                "url": url,
+                "method": method,
+                "headers": {},
+                "postData": body,
+                "initialPriority": "High",
+                "referrerPolicy": "no-referrer",
+            },
+        })).unwrap()
+    }
+
+    fn body(action: RouteAction) -> Option<Vec<u8>> {
+        match action {
+            RouteAction::Fulfill(fulfillment) => Some(fulfillment.body),
+            _ => None,
+        }
+    }
+
+    fn archive(entries: Vec<HarEntry>) -> Har {
+        let mut har = Har::new();
+        har.log.entries = entries;
+        har
+    }
+
+    #[test]
+    fn matches_by_method_and_url() {
+        let har = archive(vec![
+            entry("GET", "https://example.com/a", None, 200, Some("first")),
+            entry("GET", "https://example.com/a", None, 200, Some("second")),
+            entry("POST", "https://example.com/a", None, 200, Some("posted")),
+        ]);
+        let handler = handler(&har, ReplayOptions::new());
+
+        // the repeated requests get the responses in order, the last one is repeated:
+        assert_eq!(body(handler(&paused("GET", "https://example.com/a#top", None))), Some(b"first".to_vec()));
+        assert_eq!(body(handler(&paused("get", "https://example.com/a", None))), Some(b"second".to_vec()));
+        assert_eq!(body(handler(&paused("GET", "https://example.com/a", None))), Some(b"second".to_vec()));
+        assert_eq!(body(handler(&paused("POST", "https://example.com/a", Some("ignored")))), Some(b"posted".to_vec()));
+    }
+
+    #[test]
+    fn matches_by_body_if_enabled() {
+        let har = archive(vec![
+            entry("POST", "https://example.com/q", Some("a=1"), 200, Some("one")),
+            entry("POST", "https://example.com/q", Some("a=2"), 200, Some("two")),
+        ]);
+        let handler = handler(&har, ReplayOptions::new().match_body(true));
+
+        assert_eq!(body(handler(&paused("POST", "https://example.com/q", Some("a=2")))), Some(b"two".to_vec()));
+        assert_eq!(body(handler(&paused("POST", "https://example.com/q", Some("a=1")))), Some(b"one".to_vec()));
+        assert!(matches!(handler(&paused("POST", "https://example.com/q", Some("a=3"))), RouteAction::Abort(_)));
+    }
+
+    #[test]
+    fn applies_unmatched_policy() {
+        let har = archive(vec![entry("GET", "https://example.com/", None, 200, Some("ok"))]);
+
+        let offline = handler(&har, ReplayOptions::new());
+        assert!(matches!(offline(&paused("GET", "https://example.com/other", None)), RouteAction::Abort(Network::ErrorReason::InternetDisconnected)));
+
+        let online = handler(&har, ReplayOptions::new().unmatched(Unmatched::Continue));
+        assert!(matches!(online(&paused("GET", "https://example.com/other", None)), RouteAction::Continue));
+    }
+
+    #[test]
+    fn treats_entries_without_body_as_unmatched() {
+        let har = archive(vec![
+            entry("GET", "https://example.com/page", None, 200, None),
+            entry("GET", "https://example.com/old", None, 302, None),
+        ]);
+        let handler = handler(&har, ReplayOptions::new().unmatched(Unmatched::Continue));
+
+        assert!(matches!(handler(&paused("GET", "https://example.com/page", None)), RouteAction::Continue));
+        assert_eq!(body(handler(&paused("GET", "https://example.com/old", None))), Some(vec![]));
+    }
+}
@@ -0,0 +1,122 @@
+use crate::prelude::*;
+use crate::cdp::{ CdpSession, EventStream, RawEvent };
+use super::{ glob_match, HarPair };
+
+use std::collections::HashMap;
+use base64::{ Engine, engine::general_purpose::STANDARD as BASE64 };
+use futures::stream::{ self, StreamExt };
+use serde_json::json;
+
+/// The captured response with its body
+#[derive(Debug, Clone, PartialEq)]
+pub struct CapturedResponse {
+    pub url: String,
+    pub status: u16,
+    pub status_text: String,
+    pub headers: Vec<(String, String)>,
+    pub mime_type: String,
+    /// The resource type (e.g. 'XHR', 'Fetch', 'Document')
+    pub resource_type: String,
+    /// The decoded body (None = the body is not captured, e.g. evicted from the browser buffer or a redirect)
+    pub body: Option<Vec<u8>>,
+}
+
+impl CapturedResponse {
+    /// Returns the header value (case-insensitive)
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Returns the body as UTF-8 text (invalid chars are replaced)
+    pub fn text(&self) -> Option<String> {
+        self.body.as_ref().map(|body| String::from_utf8_lossy(body).into_owned())
+    }
+
+    /// Parses the JSON body
+    pub fn json<D: serde::de::DeserializeOwned>(&self) -> Result<D> {
+        let body = self.body.as_ref().ok_or(Error::ResponseBodyUnavailable)?;
+        Ok(serde_json::from_slice(body)?)
+    }
+}
+
+/// The responses capturing state
+struct Capture {
+    cdp: CdpSession,
+    events: EventStream<RawEvent>,
+    pattern: String,
+    /// The matched responses waiting for the body (request id -> response)
+    pending: HashMap<String, CapturedResponse>,
+}
+
+impl Capture {
+    /// Handles the network event, returns the response when its body is loaded
+    async fn handle(&mut self, event: RawEvent) -> Option<CapturedResponse> {
+        let params = &event.params;
+        let request_id = params["requestId"].as_str()?.to_owned();
+
+        match event.method.as_str() {
+            "Network.responseReceived" => {
+                let response = &params["response"];
+                let url = response["url"].as_str().unwrap_or_default();
+                if !glob_match(&self.pattern, url) { return None; }
+
+                self.pending.insert(request_id, CapturedResponse {
+                    url: url.to_owned(),
+                    status: response["status"].as_u64().unwrap_or_default() as u16,
+                    status_text: response["statusText"].as_str().unwrap_or_default().to_owned(),
+                    headers: HarPair::from_headers(&response["headers"])
+                        .into_iter()
+                        .map(|pair| (pair.name, pair.value))
+                        .collect(),
+                    mime_type: response["mimeType"].as_str().unwrap_or_default().to_owned(),
+                    resource_type: params["type"].as_str().unwrap_or_default().to_owned(),
+                    body: None,
+                });
+                None
+            },
+
+            "Network.loadingFinished" => {
+                let mut response = self.pending.remove(&request_id)?;
+                response.body = body(&self.cdp, &request_id).await.ok();
+                Some(response)
+            },
+
+            "Network.loadingFailed" => {
+                self.pending.remove(&request_id);
+                None
+            },
+
+            _ => None,
+        }
+    }
+}
+
+/// Loads the response body
+async fn body(cdp: &CdpSession, request_id: &str) -> Result<Vec<u8>> {
+    let result = cdp.send("Network.getResponseBody", json!({ "requestId": request_id })).await?;
+    let body = result["body"].as_str().unwrap_or_default();
+
+    match result["base64Encoded"].as_bool() {
+        Some(true) => Ok(BASE64.decode(body)?),
+        _ => Ok(body.as_bytes().to_vec()),
+    }
+}
+
+/// Returns the stream of the responses matching the URL glob pattern
+pub(crate) async fn responses(cdp: CdpSession, pattern: String) -> Result<EventStream<CapturedResponse>> {
+    let events = cdp.subscribe(&["Network"]).await?;
+
+    let capture = Capture { cdp, events, pattern, pending: HashMap::new() };
+
+    Ok(stream::unfold(capture, |mut capture| async move {
+        while let Some(event) = capture.events.next().await {
+            if let Some(response) = capture.handle(event).await {
+                return Some((response, capture));
+            }
+        }
+        None
+    }).boxed())
+}
+
@@ -0,0 +1,431 @@
+use crate::prelude::*;
+use crate::cdp::{ CdpSession, Fetch, Network };
+use super::{ glob_match, BlockRules, BlockStats, ProxyCredentials };
+
+use base64::{ Engine, engine::general_purpose::STANDARD as BASE64 };
+use futures::StreamExt;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::sync::{ Weak, RwLock };
+use tokio::task::JoinHandle;
+
+/// The max count of remembered authenticated requests
+const MAX_AUTHENTICATED: usize = 10_000;
+
+/// The route handler (decides what to do with the paused request)
+pub type RouteHandler = Arc<dyn Fn(&Fetch::RequestPaused) -> RouteAction + Send + Sync>;
+
+/// The action applied to the intercepted request
+#[derive(Debug, Clone)]
+pub enum RouteAction {
+    /// Sends the request as is
+    Continue,
+    /// Sends the request with modified parameters
+    ContinueWith(Overrides),
+    /// Responds to the request without sending it
+    Fulfill(Fulfillment),
+    /// Fails the request with the network error
+    Abort(Network::ErrorReason),
+}
+
+/// The request parameters override
+#[derive(Debug, Clone, Default)]
+pub struct Overrides {
+    pub url: Option<String>,
+    pub method: Option<String>,
+    /// The request headers (replace all original headers)
+    pub headers: Option<Vec<(String, String)>>,
+    pub post_data: Option<Vec<u8>>,
+}
+
+impl Overrides {
+    /// Creates an empty override
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Overrides the request URL (the change is not visible to the page)
+    pub fn url<S: Into<String>>(mut self, url: S) -> Self {
+        self.url = Some(url.into());
+        self
+    }
+
+    /// Overrides the request method
+    pub fn method<S: Into<String>>(mut self, method: S) -> Self {
+        self.method = Some(method.into());
+        self
+    }
+
+    /// Replaces the request headers
+    pub fn headers<K: Into<String>, V: Into<String>>(mut self, headers: impl IntoIterator<Item = (K, V)>) -> Self {
+        self.headers = Some(headers.into_iter().map(|(k, v)| (k.into(), v.into())).collect());
+        self
+    }
+
+    /// Overrides the request body
+    pub fn post_data<B: Into<Vec<u8>>>(mut self, body: B) -> Self {
+        self.post_data = Some(body.into());
+        self
+    }
+}
+
+/// The response to the intercepted request
+#[derive(Debug, Clone)]
+pub struct Fulfillment {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Fulfillment {
+    /// Creates an empty response with the status code
+    pub fn new(status: u16) -> Self {
+        Self {
+            status,
+            headers: vec![],
+            body: vec![],
+        }
+    }
+
+    /// Appends the response header
+    pub fn header<K: Into<String>, V: Into<String>>(mut self, name: K, value: V) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Sets the response body
+    pub fn body<B: Into<Vec<u8>>>(mut self, body: B) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    /// Sets the text response body
+    pub fn text<S: Into<String>>(self, text: S) -> Self {
+        self.header("Content-Type", "text/plain; charset=utf-8")
+            .body(text.into())
+    }
+
+    /// Sets the JSON response body
+    pub fn json(self, value: &Value) -> Self {
+        self.header("Content-Type", "application/json")
+            .body(value.to_string())
+    }
+}
+
+/// The route owner (the built-in features replace only their own routes)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RouteKind {
+    User,
+    Blocking,
+    Replay,
+}
+
+/// The registered route
+#[derive(Clone)]
+struct Route {
+    pattern: String,
+    resource_type: Option<Network::ResourceType>,
+    handler: RouteHandler,
+    kind: RouteKind,
+}
+
+/// The intercepted request event
+enum Interception {
+    Request(Fetch::RequestPaused),
+    Auth(Fetch::AuthRequired),
+}
+
+/// The tab requests router (owns the CDP 'Fetch' domain of the tab)
+pub(crate) struct Router {
+    cdp: CdpSession,
+    routes: RwLock<Vec<Route>>,
+    stats: Arc<std::sync::Mutex<BlockStats>>,
+    /// The proxy credentials (all requests are intercepted to answer the auth challenges)
+    credentials: RwLock<Option<ProxyCredentials>>,
+    /// The requests already answered with credentials (the repeated challenge means wrong credentials)
+    authenticated: std::sync::Mutex<HashSet<String>>,
+    task: std::sync::Mutex<Option<JoinHandle<()>>>,
+}
+
+impl Router {
+    /// Creates a new router and starts handling paused requests
+    /// * previous: the router stopped with the lost connection (its routes, credentials & stats are taken over)
+    pub(crate) async fn new(cdp: CdpSession, previous: Option<&Router>) -> Result<Arc<Self>> {
+        let paused = cdp.events::<Fetch::RequestPaused>().await?.map(Interception::Request);
+        let auth = cdp.events::<Fetch::AuthRequired>().await?.map(Interception::Auth);
+        let mut events = futures::stream::select(paused, auth);
+
+        let router = Arc::new(Self {
+            cdp,
+            routes: RwLock::new(previous.map(|p| p.routes.read().unwrap().clone()).unwrap_or_default()),
+            stats: previous.map(|p| p.stats.clone()).unwrap_or_default(),
+            credentials: RwLock::new(previous.and_then(|p| p.credentials.read().unwrap().clone())),
+            authenticated: std::sync::Mutex::new(HashSet::new()),
+            task: std::sync::Mutex::new(None),
+        });
+
+        let weak: Weak<Self> = Arc::downgrade(&router);
+        let task = tokio::spawn(async move {
+            while let Some(event) = events.next().await {
+                let Some(router) = weak.upgrade() else { break };
+
+                match event {
+                    Interception::Request(event) => router.handle(event).await,
+                    Interception::Auth(event) => router.authenticate(event).await,
+                }
+            }
+        });
+        *router.task.lock().unwrap() = Some(task);
+
+        if previous.is_some() {
+            router.refresh().await?;
+        }
+
+        Ok(router)
+    }
+
+    /// Returns true if the router is stopped (the events stream is ended with the lost connection)
+    pub(crate) fn is_stopped(&self) -> bool {
+        self.task.lock().unwrap().as_ref().is_none_or(|task| task.is_finished())
+    }
+
+    /// Registers the route (the last registered route is checked first, but after the blocking routes)
+    pub(crate) async fn add(&self, pattern: String, resource_type: Option<Network::ResourceType>, handler: RouteHandler) -> Result<()> {
+        self.routes.write().unwrap().push(Route { pattern, resource_type, handler, kind: RouteKind::User });
+        self.refresh().await
+    }
+
+    /// Replaces the blocking routes (the blocked requests are counted in the stats)
+    pub(crate) async fn block(&self, rules: &BlockRules) -> Result<()> {
+        let stats = self.stats.clone();
+        let handler: RouteHandler = Arc::new(move |event| {
+            stats.lock().unwrap().count(event.resource_type);
+            RouteAction::Abort(Network::ErrorReason::BlockedByClient)
+        });
+
+        let routes = rules.resource_types.iter()
+            .map(|t| (str!("*"), Some(*t), handler.clone()))
+            .chain(rules.patterns.iter().map(|p| (p.clone(), None, handler.clone())))
+            .collect();
+
+        self.replace(RouteKind::Blocking, routes).await
+    }
+
+    /// Replaces all routes of the built-in feature
+    pub(crate) async fn replace(&self, kind: RouteKind, new_routes: Vec<(String, Option<Network::ResourceType>, RouteHandler)>) -> Result<()> {
+        {
+            let mut routes = self.routes.write().unwrap();
+            routes.retain(|route| route.kind != kind);
+
+            for (pattern, resource_type, handler) in new_routes {
+                routes.push(Route { pattern, resource_type, handler, kind });
+            }
+        }
+
+        self.refresh().await
+    }
+
+    /// Returns the blocked requests statistics
+    pub(crate) fn stats(&self) -> BlockStats {
+        self.stats.lock().unwrap().clone()
+    }
+
+    /// Sets the proxy credentials (None = do not answer the auth challenges)
+    pub(crate) async fn set_credentials(&self, credentials: Option<ProxyCredentials>) -> Result<()> {
+        *self.credentials.write().unwrap() = credentials;
+        self.refresh().await
+    }
+
+    /// Removes all user routes with the pattern
+    pub(crate) async fn remove(&self, pattern: &str) -> Result<()> {
+        self.routes.write().unwrap().retain(|route| route.kind != RouteKind::User || route.pattern != pattern);
+        self.refresh().await
+    }
+
+    /// Removes all user routes
+    pub(crate) async fn clear(&self) -> Result<()> {
+        self.routes.write().unwrap().retain(|route| route.kind != RouteKind::User);
+        self.refresh().await
+    }
+
+    /// Updates the intercepted request patterns
+    async fn refresh(&self) -> Result<()> {
+        let authenticate = self.credentials.read().unwrap().is_some();
+        let mut patterns = self.routes.read().unwrap()
+            .iter()
+            .map(|route| Fetch::RequestPattern {
+                url_pattern: Some(route.pattern.clone()),
+                resource_type: route.resource_type,
+                request_stage: Some(Fetch::RequestStage::Request),
+            })
+            .collect::<Vec<_>>();
+
+        // the auth challenges are reported for the intercepted requests only:
+        if authenticate {
+            patterns.push(Fetch::RequestPattern {
+                url_pattern: Some(str!("*")),
+                resource_type: None,
+                request_stage: Some(Fetch::RequestStage::Request),
+            });
+        }
+
+        if patterns.is_empty() {
+            self.cdp.execute(Fetch::Disable {}).await?;
+        } else {
+            self.cdp.execute(Fetch::Enable { patterns: Some(patterns), handle_auth_requests: Some(authenticate) }).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Finds the route for the request and returns its action
+    fn action(&self, event: &Fetch::RequestPaused) -> RouteAction {
+        let routes = self.routes.read().unwrap();
+
+        match find_route(&routes, event.resource_type, &event.request.url) {
+            Some(route) => (route.handler)(event),
+            None => RouteAction::Continue,
+        }
+    }
+
+    /// Handles the paused request
+    async fn handle(&self, event: Fetch::RequestPaused) {
+        let request_id = event.request_id.clone();
+        let action = self.action(&event);
+
+        if self.apply(&request_id, action).await.is_err() {
+            // the request must not stay paused:
+            let _ = self.cdp.execute(Fetch::ContinueRequest { request_id, ..Default::default() }).await;
+        }
+    }
+
+    /// Answers the auth challenge (the proxy challenges only)
+    async fn authenticate(&self, event: Fetch::AuthRequired) {
+        let credentials = self.credentials.read().unwrap().clone();
+        let is_proxy = event.auth_challenge.source == Some(Fetch::AuthChallengeSource::Proxy);
+
+        let response = match credentials {
+            Some(credentials) if is_proxy => {
+                let mut authenticated = self.authenticated.lock().unwrap();
+
+                // the successful requests are never reported again, so keep the set bounded:
+                if authenticated.len() >= MAX_AUTHENTICATED {
+                    authenticated.clear();
+                }
+
+                // answered already, so the credentials are wrong:
+                if !authenticated.insert(event.request_id.clone()) {
+                    authenticated.remove(&event.request_id);
+                    Fetch::AuthChallengeResponse { response: Fetch::AuthChallengeResponseResponse::CancelAuth, username: None, password: None }
+                } else {
+                    Fetch::AuthChallengeResponse {
+                        response: Fetch::AuthChallengeResponseResponse::ProvideCredentials,
+                        username: Some(credentials.username),
+                        password: Some(credentials.password),
+                    }
+                }
+            },
+            _ => Fetch::AuthChallengeResponse { response: Fetch::AuthChallengeResponseResponse::Default, username: None, password: None },
+        };
+
+        let _ = self.cdp.execute(Fetch::ContinueWithAuth { request_id: event.request_id, auth_challenge_response: response }).await;
+    }
+
+    /// Applies the action to the paused request
+    async fn apply(&self, request_id: &str, action: RouteAction) -> Result<()> {
+        let request_id = request_id.to_owned();
+        let entries = |headers: Vec<(String, String)>| {
+            headers.into_iter()
+                .map(|(name, value)| Fetch::HeaderEntry { name, value })
+                .collect::<Vec<_>>()
+        };
+
+        match action {
+            RouteAction::Continue => {
+                self.cdp.execute(Fetch::ContinueRequest { request_id, ..Default::default() }).await?;
+            },
+            RouteAction::ContinueWith(overrides) => {
+                self.cdp.execute(Fetch::ContinueRequest {
+                    request_id,
+                    url: overrides.url,
+                    method: overrides.method,
+                    post_data: overrides.post_data.map(|body| BASE64.encode(body)),
+                    headers: overrides.headers.map(entries),
+                    intercept_response: None,
+                }).await?;
+            },
+            RouteAction::Fulfill(response) => {
+                self.cdp.execute(Fetch::FulfillRequest {
+                    request_id,
+                    response_code: response.status as i64,
+                    response_headers: Some(entries(response.headers)),
+                    binary_response_headers: None,
+                    body: Some(BASE64.encode(response.body)),
+                    response_phrase: None,
+                }).await?;
+            },
+            RouteAction::Abort(reason) => {
+                self.cdp.execute(Fetch::FailRequest { request_id, error_reason: reason }).await?;
+            },
+        }
+
+        Ok(())
+    }
+}
+
+/// Finds the route of the request: the blocking routes are checked first (nothing can unblock a request),
+/// then the other routes from the last registered
+fn find_route<'a>(routes: &'a [Route], resource_type: Network::ResourceType, url: &str) -> Option<&'a Route> {
+    let matches = |route: &&Route| route.resource_type.is_none_or(|t| t == resource_type) && glob_match(&route.pattern, url);
+
+    routes.iter()
+        .filter(|route| route.kind == RouteKind::Blocking)
+        .find(matches)
+        .or_else(|| routes.iter().rev().filter(|route| route.kind != RouteKind::Blocking).find(matches))
+}
+
+impl Drop for Router {
+    fn drop(&mut self) {
+        if let Some(task) = self.task.lock().unwrap().take() {
+            task.abort();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Network::ResourceType;
+
+    fn route(pattern: &str, resource_type: Option<ResourceType>, kind: RouteKind) -> Route {
+        Route { pattern: pattern.to_owned(), resource_type, handler: Arc::new(|_| RouteAction::Continue), kind }
+    }
+
+    #[test]
+    fn blocking_routes_take_precedence() {
+        // the replay & user routes are registered after the blocking rules:
+        let routes = vec![
+            route("*", Some(ResourceType::Image), RouteKind::Blocking),
+            route("*://ads.example.com/*", None, RouteKind::Blocking),
+            route("*", None, RouteKind::Replay),
+            route("*://ads.example.com/*", None, RouteKind::User),
+        ];
+        let kind = |resource_type, url| find_route(&routes, resource_type, url).map(|route| route.kind);
+
+        assert_eq!(kind(ResourceType::Image, "https://example.com/logo.png"), Some(RouteKind::Blocking));
+        assert_eq!(kind(ResourceType::Script, "https://ads.example.com/ad.js"), Some(RouteKind::Blocking));
+        assert_eq!(kind(ResourceType::Document, "https://example.com/"), Some(RouteKind::Replay));
+    }
+
+    #[test]
+    fn last_registered_route_is_checked_first() {
+        let routes = vec![
+            route("*", None, RouteKind::Replay),
+            route("*/api/*", None, RouteKind::User),
+        ];
+        let kind = |url| find_route(&routes, ResourceType::Fetch, url).map(|route| route.kind);
+
+        assert_eq!(kind("https://example.com/api/users"), Some(RouteKind::User));
+        assert_eq!(kind("https://example.com/index.js"), Some(RouteKind::Replay));
+    }
+}
@@ -0,0 +1,97 @@
+use crate::cdp::Browser;
+
+/// The browser permission
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Permission {
+    Geolocation,
+    Notifications,
+    ClipboardRead,
+    ClipboardWrite,
+    Camera,
+    Microphone,
+    Midi,
+    MidiSysex,
+    BackgroundSync,
+    PersistentStorage,
+    StorageAccess,
+    ScreenWakeLock,
+    IdleDetection,
+    LocalFonts,
+    WindowManagement,
+    DisplayCapture,
+    /// The other permission by its W3C name (e.g. 'accelerometer', 'gyroscope')
+    Other(String),
+}
+
+impl Permission {
+    /// Returns the W3C permission descriptor name (e.g. 'clipboard-read')
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Geolocation => "geolocation",
+            Self::Notifications => "notifications",
+            Self::ClipboardRead => "clipboard-read",
+            Self::ClipboardWrite => "clipboard-write",
+            Self::Camera => "camera",
+            Self::Microphone => "microphone",
+            Self::Midi | Self::MidiSysex => "midi",
+            Self::BackgroundSync => "background-sync",
+            Self::PersistentStorage => "persistent-storage",
+            Self::StorageAccess => "storage-access",
+            Self::ScreenWakeLock => "screen-wake-lock",
+            Self::IdleDetection => "idle-detection",
+            Self::LocalFonts => "local-fonts",
+            Self::WindowManagement => "window-management",
+            Self::DisplayCapture => "display-capture",
+            Self::Other(name) => name,
+        }
+    }
+
+    /// Converts to the CDP permission descriptor
+    pub(crate) fn to_descriptor(&self) -> Browser::PermissionDescriptor {
+        Browser::PermissionDescriptor {
+            name: self.name().to_owned(),
+            sysex: matches!(self, Self::MidiSysex).then_some(true),
+            ..Default::default()
+        }
+    }
+
+    /// Converts to the W3C 'POST /permissions' descriptor
+    pub(crate) fn to_json(&self) -> serde_json::Value {
+        let mut descriptor = serde_json::json!({ "name": self.name() });
+        if matches!(self, Self::MidiSysex) {
+            descriptor["sysex"] = true.into();
+        }
+
+        descriptor
+    }
+}
+
+/// The browser permission state
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum PermissionState {
+    #[default]
+    Granted,
+    Denied,
+    /// Asks the user (the default browser behavior)
+    Prompt,
+}
+
+impl PermissionState {
+    /// Returns the W3C state name
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Granted => "granted",
+            Self::Denied => "denied",
+            Self::Prompt => "prompt",
+        }
+    }
+
+    /// Converts to the CDP permission setting
+    pub(crate) fn to_setting(self) -> Browser::PermissionSetting {
+        match self {
+            Self::Granted => Browser::PermissionSetting::Granted,
+            Self::Denied => Browser::PermissionSetting::Denied,
+            Self::Prompt => Browser::PermissionSetting::Prompt,
+        }
+    }
+}
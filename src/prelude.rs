@@ -9,3 +9,4 @@ pub(crate) use std::sync::Arc;
 pub(crate) use std::time::Duration;
 pub(crate) use tokio::sync::{ Mutex, Notify };
 pub(crate) use tokio::time::sleep;
+pub(crate) use reqwest::Method;
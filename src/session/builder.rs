@@ -0,0 +1,424 @@
+use crate::prelude::*;
+use super::*;
+use crate::emulation::{ Device, Geolocation, UserAgent };
+use crate::network::{ BlockRules, Proxy, ProxyAuth };
+
+use std::process::{ Command, Stdio };
+use reqwest::Client;
+use serde_json::{ json, Value };
+
+/// The chromedriver session launch options
+#[derive(Debug, Clone)]
+pub struct SessionBuilder {
+    port: u16,
+    chromedriver_path: PathBuf,
+    profile_path: Option<PathBuf>,
+    headless: bool,
+    args: Vec<String>,
+    timeouts: Timeouts,
+    request_timeout: Option<Duration>,
+    page_load_strategy: Option<PageLoadStrategy>,
+    bidi: bool,
+    prefs: serde_json::Map<String, Value>,
+    logging_prefs: serde_json::Map<String, Value>,
+    proxy: Option<Proxy>,
+    mobile_emulation: Option<Value>,
+    tab_defaults: TabDefaults,
+}
+
+impl SessionBuilder {
+    /// Creates new launch options
+    /// * port: a new chromedriver session IP-port
+    /// * chromedriver_path: path to chromedriver
+    pub fn new<P: Into<PathBuf>>(port: u16, chromedriver_path: P) -> Self {
+        Self {
+            port,
+            chromedriver_path: chromedriver_path.into(),
+            profile_path: None,
+            headless: false,
+            args: vec![],
+            timeouts: Timeouts::new(),
+            request_timeout: None,
+            page_load_strategy: None,
+            bidi: false,
+            prefs: serde_json::Map::new(),
+            logging_prefs: serde_json::Map::new(),
+            proxy: None,
+            mobile_emulation: None,
+            tab_defaults: TabDefaults::default(),
+        }
+    }
+
+    /// Sets path to storage user profile (None = do not save session)
+    pub fn profile(mut self, profile_path: Option<PathBuf>) -> Self {
+        self.profile_path = profile_path;
+        self
+    }
+
+    /// Runs as headless mode (without interface)
+    pub fn headless(mut self, headless: bool) -> Self {
+        self.headless = headless;
+        self
+    }
+
+    /// Appends a chrome command line argument (e.g. '--window-size=1280,720')
+    pub fn arg<S: Into<String>>(mut self, arg: S) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    /// Sets the W3C session timeouts
+    pub fn timeouts(mut self, timeouts: Timeouts) -> Self {
+        self.timeouts = timeouts;
+        self
+    }
+
+    /// Sets the script evaluation timeout
+    pub fn script_timeout(mut self, timeout: Duration) -> Self {
+        self.timeouts.script = Some(timeout);
+        self
+    }
+
+    /// Sets the page loading timeout
+    pub fn page_load_timeout(mut self, timeout: Duration) -> Self {
+        self.timeouts.page_load = Some(timeout);
+        self
+    }
+
+    /// Sets the implicit element location timeout
+    pub fn implicit_timeout(mut self, timeout: Duration) -> Self {
+        self.timeouts.implicit = Some(timeout);
+        self
+    }
+
+    /// Sets the client-side timeout of every request to chromedriver
+    pub fn request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the page load strategy (what chromedriver waits for on navigation)
+    pub fn page_load_strategy(mut self, strategy: PageLoadStrategy) -> Self {
+        self.page_load_strategy = Some(strategy);
+        self
+    }
+
+    /// Requests the WebDriver BiDi WebSocket ('webSocketUrl' capability)
+    pub fn bidi(mut self, enable: bool) -> Self {
+        self.bidi = enable;
+        self
+    }
+
+    /// Sets a chrome preference (e.g. 'intl.accept_languages')
+    pub fn pref<K: Into<String>>(mut self, key: K, value: Value) -> Self {
+        self.prefs.insert(key.into(), value);
+        self
+    }
+
+    /// Disables images loading by the chrome preference
+    pub fn disable_images(self, disable: bool) -> Self {
+        self.pref("profile.managed_default_content_settings.images", json!(if disable { 2 } else { 1 }))
+    }
+
+    /// Sets the log level of the log type (e.g. 'performance', 'ALL')
+    pub fn log_level<T: Into<String>, L: Into<String>>(mut self, log_type: T, level: L) -> Self {
+        self.logging_prefs.insert(log_type.into(), json!(level.into()));
+        self
+    }
+
+    /// Enables the performance log (the CDP Network & Page events, see 'Tab::drain_performance_log')
+    pub fn performance_log(self, enable: bool) -> Self {
+        self.log_level("performance", if enable { "ALL" } else { "OFF" })
+    }
+
+    /// Enables the browser log (console messages & errors without DevTools WebSocket, see 'Session::drain_browser_log')
+    pub fn browser_log(self, enable: bool) -> Self {
+        self.log_level("browser", if enable { "ALL" } else { "OFF" })
+    }
+
+    /// Sets the default blocking rules of the launched tabs & the tabs opened by 'Session::open'
+    pub fn block(mut self, rules: BlockRules) -> Self {
+        self.tab_defaults.block_rules = Some(rules);
+        self
+    }
+
+    /// Sets the user agent ('--user-agent' argument), the full override (headers, platform & client hints)
+    /// is applied to the launched tabs & the tabs opened by 'Session::open'
+    pub fn user_agent(mut self, user_agent: UserAgent) -> Self {
+        self.tab_defaults.user_agent = Some(user_agent);
+        self
+    }
+
+    /// Appends the extra HTTP header sent by the launched tabs & the tabs opened by 'Session::open'
+    pub fn extra_header<K: Into<String>, V: Into<String>>(mut self, name: K, value: V) -> Self {
+        self.tab_defaults.extra_headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Sets the proxy settings (the credentials are answered by the selected 'ProxyAuth' method)
+    pub fn proxy(mut self, proxy: Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Emulates the device in all tabs (the chromedriver 'mobileEmulation' option)
+    pub fn device(mut self, device: &Device) -> Self {
+        self.mobile_emulation = Some(device.to_mobile_emulation());
+        self
+    }
+
+    /// Emulates the device from the chromedriver devices list (e.g. 'Nexus 5')
+    pub fn device_name<S: Into<String>>(mut self, name: S) -> Self {
+        self.mobile_emulation = Some(json!({ "deviceName": name.into() }));
+        self
+    }
+
+    /// Emulates the geolocation in the launched tabs & the tabs opened by 'Session::open' (the permission is granted automatically)
+    pub fn geolocation(mut self, geolocation: Geolocation) -> Self {
+        self.tab_defaults.geolocation = Some(geolocation);
+        self
+    }
+
+    /// Sets the timezone (IANA id, e.g. 'Europe/Berlin') of all tabs, the chrome process gets the 'TZ' variable too
+    pub fn timezone<S: Into<String>>(mut self, timezone: S) -> Self {
+        self.tab_defaults.timezone = Some(timezone.into());
+        self
+    }
+
+    /// Sets the locale (BCP 47 tag, e.g. 'de-DE') of all tabs, the chrome gets the '--lang' argument too
+    pub fn locale<S: Into<String>>(mut self, locale: S) -> Self {
+        self.tab_defaults.locale = Some(locale.into());
+        self
+    }
+
+    /// Collects the console messages & JavaScript errors of the launched tabs & the tabs opened by 'Session::open'
+    /// from their start (see 'Tab::console_messages')
+    pub fn collect_console(mut self, enable: bool) -> Self {
+        self.tab_defaults.console = enable;
+        self
+    }
+
+    /// Builds the HTTP client
+    fn client(&self) -> Result<Client> {
+        let mut client = Client::builder();
+        if let Some(timeout) = self.request_timeout {
+            client = client.timeout(timeout);
+        }
+
+        Ok(client.build()?)
+    }
+
+    /// Run chromedriver session in new window
+    pub async fn run(self) -> Result<Session> {
+        // the headless chrome ignores the extensions, so the proxy auth would silently not happen:
+        if self.headless && let Some(proxy) = &self.proxy && proxy.credentials.is_some() && proxy.auth == ProxyAuth::Extension {
+            return Err(Error::ProxyExtensionHeadless.into());
+        }
+
+        // get path to chromedriver:
+        let mut cmd = Command::new(&self.chromedriver_path);
+        let port = self.port;
+
+        // starting chromedriver server as background process:
+        cmd.arg(fmt!("--port={port}"))
+            .arg("--silent")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+
+        // settings for launching without a terminal window:
+        #[cfg(windows)]
+        {
+            use std::os::windows::process::CommandExt;
+            cmd.creation_flags(0x08000000);
+        }
+        #[cfg(unix)]
+        {
+            cmd.stdin(Stdio::null());
+        }
+
+        // the timezone of the chrome process (inherited from chromedriver):
+        if let Some(timezone) = &self.tab_defaults.timezone {
+            cmd.env("TZ", timezone);
+        }
+
+        let _ = cmd.spawn()?;
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;  // waiting when chromedriver is initializes..
+
+        // init request options:
+        let mut options = json!({
+            "browserName": "chrome"
+        });
+
+        // loading & saving profile data + headless mode:
+        let mut args = vec![];
+        if let Some(path) = &self.profile_path {
+            let path = path
+                .to_str()
+                .ok_or(Error::InvalidPath)?
+                .to_owned();
+
+            args.push(fmt!("--user-data-dir={path}"));
+            args.push(str!("--disable-cache"));
+            args.push(str!("--disk-cache-size=1"));
+        }
+
+        // append headless mode:
+        if self.headless {
+            args.push("--headless".to_string());
+            args.push("--disable-gpu".to_string());
+        }
+
+        // append user agent:
+        if let Some(user_agent) = &self.tab_defaults.user_agent {
+            args.push(fmt!("--user-agent={}", user_agent.user_agent));
+        }
+        if let Some(languages) = self.tab_defaults.user_agent.as_ref().and_then(|ua| ua.accept_language.as_ref()) {
+            args.push(fmt!("--accept-lang={languages}"));
+        }
+
+        // append proxy:
+        let mut extension_dir = None;
+        if let Some(proxy) = &self.proxy {
+            args.extend(proxy.to_args());
+
+            if proxy.credentials.is_some() && proxy.auth == ProxyAuth::Extension {
+                // the credentials are kept in a private (0700) randomly named directory:
+                let dir = tempfile::Builder::new().prefix("chromedriver-api-proxy-").tempdir()?;
+                proxy.write_extension(dir.path()).await?;
+                args.push(fmt!("--load-extension={}", dir.path().to_str().ok_or(Error::InvalidPath)?));
+
+                extension_dir = Some(dir);
+            }
+        }
+
+        // append locale:
+        if let Some(locale) = &self.tab_defaults.locale {
+            args.push(fmt!("--lang={locale}"));
+        }
+
+        // append custom arguments:
+        args.extend(self.args.iter().cloned());
+        options["goog:chromeOptions"] = json!({ "args": args });
+
+        // append mobile emulation:
+        if let Some(emulation) = &self.mobile_emulation {
+            options["goog:chromeOptions"]["mobileEmulation"] = emulation.clone();
+        }
+
+        // append preferences:
+        if !self.prefs.is_empty() {
+            options["goog:chromeOptions"]["prefs"] = Value::Object(self.prefs.clone());
+        }
+
+        // append logging preferences:
+        if !self.logging_prefs.is_empty() {
+            options["goog:loggingPrefs"] = Value::Object(self.logging_prefs.clone());
+        }
+
+        // append proxy capability:
+        if let Some(capability) = self.proxy.as_ref().and_then(|proxy| proxy.to_capability()) {
+            options["proxy"] = capability;
+        }
+
+        // append timeouts:
+        if !self.timeouts.is_empty() {
+            options["timeouts"] = self.timeouts.to_json();
+        }
+
+        // append page load strategy:
+        if let Some(strategy) = self.page_load_strategy {
+            options["pageLoadStrategy"] = json!(strategy.as_str());
+        }
+
+        // request BiDi WebSocket:
+        if self.bidi {
+            options["webSocketUrl"] = json!(true);
+        }
+
+        // disable automation warning:
+        #[cfg(feature = "no-automation")]
+        {
+            options["goog:chromeOptions"]["excludeSwitches"] = json!(["enable-automation"]);
+            options["goog:chromeOptions"]["useAutomationExtension"] = json!(false);
+            options["goog:chromeOptions"]["args"].as_array_mut().unwrap().extend([
+                json!("--disable-blink-features=AutomationControlled"),
+            ]);
+        }
+
+        // init client:
+        let client = self.client()?;
+        let session_url = fmt!("http://127.0.0.1:{port}/session");
+
+        // send request:
+        let response = client
+            .post(&session_url)
+            .json(&json!({
+                "capabilities": {
+                    "alwaysMatch": options
+                }
+            }))
+            .send()
+            .await?
+            .json::<Value>()
+            .await?;
+
+        // get session id:
+        let session_id = response["value"]["sessionId"]
+            .as_str()
+            .ok_or(Error::IncorrectSessionId)?
+            .to_string();
+
+        // get browser DevTools address:
+        let debugger_address = response["value"]["capabilities"]["goog:chromeOptions"]["debuggerAddress"]
+            .as_str()
+            .map(|s| s.to_owned());
+
+        // get BiDi WebSocket URL:
+        let bidi_url = response["value"]["capabilities"]["webSocketUrl"]
+            .as_str()
+            .map(|s| s.to_owned());
+
+        let mut session = Session {
+            client,
+            port,
+            session_id,
+            manager: Arc::new(SessionManager::new()),
+            shared: Arc::new(Shared::new(debugger_address, bidi_url)),
+        };
+
+        *session.shared.extension_dir.lock().unwrap() = extension_dir;
+
+        // answer the proxy auth challenges by CDP:
+        let mut tab_defaults = self.tab_defaults;
+        if let Some(proxy) = &self.proxy && proxy.auth == ProxyAuth::Fetch {
+            tab_defaults.proxy_credentials = proxy.credentials.clone();
+        }
+
+        // the chromedriver & browser must not be left running on errors:
+        if let Err(e) = Self::setup(&mut session, tab_defaults).await {
+            let _ = session.close().await;
+            return Err(e);
+        }
+
+        Ok(session)
+    }
+
+    /// Prepares the started session and its launched tabs
+    async fn setup(session: &mut Session, tab_defaults: TabDefaults) -> Result<()> {
+        #[cfg(feature = "no-automation")]
+        {
+            session.disable_automation().await?;
+        }
+
+        // apply the default settings to the launched tabs:
+        if tab_defaults != TabDefaults::default() {
+            for tab_id in session.get_tabs_ids().await? {
+                session.new_tab(tab_id).apply_defaults(&tab_defaults).await?;
+            }
+        }
+
+        session.shared.update_tab_defaults(|defaults| *defaults = tab_defaults);
+
+        Ok(())
+    }
+}
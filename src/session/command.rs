@@ -0,0 +1,30 @@
+use crate::prelude::*;
+
+use reqwest::Client;
+use serde_json::Value;
+
+/// Sends the WebDriver command and returns the response value
+pub(crate) async fn send(client: &Client, method: Method, url: &str, body: Option<Value>) -> Result<Value> {
+    let mut request = client.request(method, url);
+    if let Some(body) = body {
+        request = request.json(&body);
+    }
+
+    let response = request
+        .send()
+        .await?
+        .json::<Value>()
+        .await?;
+
+    let value = response.get("value")
+        .ok_or(Error::UnexpectedResponse)?
+        .to_owned();
+
+    // check for W3C error:
+    if let Some(error) = value.get("error").and_then(|e| e.as_str()) {
+        let message = value["message"].as_str().unwrap_or_default();
+        return Err(Error::CommandFailed(fmt!("{error}: {message}")).into());
+    }
+
+    Ok(value)
+}
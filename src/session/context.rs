@@ -0,0 +1,192 @@
+use crate::prelude::*;
+use crate::cdp::{ self, CdpSession, Target };
+use crate::network::ProxyCredentials;
+use crate::permission::{ Permission, PermissionState };
+use super::{ tab, Session, Tab };
+
+use serde_json::json;
+
+/// How long to wait for chromedriver to see a new context tab
+const TAB_APPEAR_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The browser context options
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContextOptions {
+    /// The context proxy server (e.g. 'http://host:8080' or 'socks5://host:1080')
+    pub proxy_server: Option<String>,
+    /// The hosts bypassing the proxy
+    pub proxy_bypass: Vec<String>,
+    /// The proxy credentials (answered for the tabs opened by 'BrowserContext::open')
+    pub proxy_credentials: Option<ProxyCredentials>,
+}
+
+impl ContextOptions {
+    /// Creates the default options (no own proxy)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the context proxy server
+    pub fn proxy_server<S: Into<String>>(mut self, server: S) -> Self {
+        self.proxy_server = Some(server.into());
+        self
+    }
+
+    /// Appends the host bypassing the proxy
+    pub fn proxy_bypass<S: Into<String>>(mut self, host: S) -> Self {
+        self.proxy_bypass.push(host.into());
+        self
+    }
+
+    /// Sets the proxy credentials
+    pub fn proxy_credentials<U: Into<String>, P: Into<String>>(mut self, username: U, password: P) -> Self {
+        self.proxy_credentials = Some(ProxyCredentials { username: username.into(), password: password.into() });
+        self
+    }
+}
+
+/// The isolated (incognito-like) browser context with separate cookies, cache & storage
+#[derive(Clone)]
+pub struct BrowserContext {
+    session: Session,
+    cdp: CdpSession,
+    context_id: String,
+    options: ContextOptions,
+}
+
+impl BrowserContext {
+    /// Creates a new browser context
+    pub(crate) async fn create(session: Session, options: ContextOptions) -> Result<Self> {
+        let cdp = session.cdp().await?;
+        let result = cdp.execute(Target::CreateBrowserContext {
+            dispose_on_detach: None,
+            proxy_server: options.proxy_server.clone(),
+            proxy_bypass_list: (!options.proxy_bypass.is_empty()).then(|| options.proxy_bypass.join(",")),
+            origins_with_universal_network_access: None,
+        }).await?;
+
+        Ok(Self {
+            session,
+            cdp,
+            context_id: result.browser_context_id,
+            options,
+        })
+    }
+
+    /// Returns the browser context id
+    pub fn get_id(&self) -> &str {
+        &self.context_id
+    }
+
+    /// Opens the URL-address on a new context tab (the tab is closed if it fails)
+    pub async fn open<S: Into<String>>(&self, url: S) -> Result<Arc<Mutex<Tab>>> {
+        let result = self.cdp.execute(Target::CreateTarget {
+            url: str!("about:blank"),
+            browser_context_id: Some(self.context_id.clone()),
+            ..Default::default()
+        }).await?;
+        let target_id = result.target_id;
+
+        // the new target must not be left open on errors:
+        match self.prepare(&target_id, url.into()).await {
+            Ok(tab) => Ok(Arc::new(Mutex::new(tab))),
+            Err(e) => {
+                let _ = self.cdp.execute(Target::CloseTarget { target_id: target_id.clone() }).await;
+                self.session.shared.forget_tab(&target_id).await;
+                Err(e)
+            }
+        }
+    }
+
+    /// Prepares the created context target and opens the URL-address on it
+    async fn prepare(&self, target_id: &str, url: String) -> Result<Tab> {
+        // wait for chromedriver to see the new window:
+        let started = std::time::Instant::now();
+        let tab_id = loop {
+            let handle = self.session.get_tabs_ids().await?
+                .into_iter()
+                .find(|handle| tab::target_id(handle) == target_id);
+
+            if let Some(handle) = handle {
+                break handle;
+            }
+            if started.elapsed() > TAB_APPEAR_TIMEOUT {
+                return Err(Error::TabNotFound(target_id.to_owned()).into());
+            }
+            sleep(Duration::from_millis(100)).await;
+        };
+
+        let mut tab = self.session.new_tab(tab_id);
+        tab.apply_defaults(&self.session.shared.tab_defaults()).await?;
+        if self.options.proxy_credentials.is_some() {
+            tab.set_proxy_credentials(self.options.proxy_credentials.clone()).await?;
+        }
+        tab.open(url).await?;
+
+        Ok(tab)
+    }
+
+    /// Returns the context tab identifiers (the window handles accepted by 'Session::get_tab')
+    pub async fn get_tabs_ids(&self) -> Result<Vec<String>> {
+        let targets = self.get_target_ids().await?;
+
+        Ok(self.session.get_tabs_ids().await?
+            .into_iter()
+            .filter(|handle| targets.iter().any(|target_id| target_id == tab::target_id(handle)))
+            .collect())
+    }
+
+    /// Returns the context tab DevTools target ids
+    async fn get_target_ids(&self) -> Result<Vec<String>> {
+        let result = self.cdp.send("Target.getTargets", json!({})).await?;
+
+        Ok(result["targetInfos"]
+            .as_array()
+            .ok_or(Error::UnexpectedResponse)?
+            .iter()
+            .filter(|info| info["type"] == "page" && info["browserContextId"] == self.context_id.as_str())
+            .filter_map(|info| info["targetId"].as_str().map(|id| id.to_owned()))
+            .collect())
+    }
+
+    /// Returns all context tabs
+    pub async fn get_tabs(&self) -> Result<Vec<Arc<Mutex<Tab>>>> {
+        Ok(self.get_tabs_ids().await?
+            .into_iter()
+            .map(|tab_id| Arc::new(Mutex::new(self.session.new_tab(tab_id))))
+            .collect())
+    }
+
+    /// Grants the permissions to the origin within the context
+    pub async fn grant_permissions<'a>(&self, origin: &str, permissions: impl IntoIterator<Item = &'a Permission>) -> Result<()> {
+        for permission in permissions {
+            self.cdp.execute(cdp::Browser::SetPermission {
+                permission: permission.to_descriptor(),
+                setting: PermissionState::Granted.to_setting(),
+                origin: Some(origin.to_owned()),
+                embedding_origin: None,
+                browser_context_id: Some(self.context_id.clone()),
+            }).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Resets all permission overrides of the context
+    pub async fn reset_permissions(&self) -> Result<()> {
+        self.cdp.execute(cdp::Browser::ResetPermissions { browser_context_id: Some(self.context_id.clone()) }).await?;
+        Ok(())
+    }
+
+    /// Closes all context tabs and wipes the context data
+    pub async fn dispose(&self) -> Result<()> {
+        let targets = self.get_target_ids().await?;
+
+        self.cdp.execute(Target::DisposeBrowserContext { browser_context_id: self.context_id.clone() }).await?;
+        for target_id in targets {
+            self.session.shared.forget_tab(&target_id).await;
+        }
+
+        Ok(())
+    }
+}
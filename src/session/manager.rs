@@ -7,6 +7,12 @@ pub struct SessionManager {
     flag: AtomFlag,
 }
 
+impl Default for SessionManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl SessionManager {
     /// Creates a new session task manager
     pub fn new() -> Self {
@@ -22,7 +28,24 @@ impl SessionManager {
 
     /// Unlocking tasks execution
     pub async fn unlock(&self) {
-        if self.flag.get() == false { return; }
+        if !self.flag.get() { return; }
         self.flag.swap(false).await;
     }
+
+    /// Locking tasks execution until the guard is dropped (the early returns & cancellations unlock too)
+    pub async fn guard(&self) -> SessionGuard<'_> {
+        self.lock().await;
+        SessionGuard { manager: self }
+    }
+}
+
+/// The session tasks lock, unlocks on drop
+pub struct SessionGuard<'a> {
+    manager: &'a SessionManager,
+}
+
+impl Drop for SessionGuard<'_> {
+    fn drop(&mut self) {
+        self.manager.flag.set(false);
+    }
 }
@@ -1,4 +1,13 @@
-pub mod manager;  pub use manager::SessionManager;
+pub(crate) mod command;
+pub(crate) mod shared;  pub(crate) use shared::{ Shared, TabDefaults };
+pub mod manager;  pub use manager::{ SessionManager, SessionGuard };
+pub mod timeouts; pub use timeouts::Timeouts;
+pub mod wait;     pub use wait::{ PageLoadStrategy, WaitUntil };
+pub(crate) use wait::PageWatcher;
 
 pub mod tab;      pub use tab::Tab;
+pub mod storage;  pub use storage::{ Storage, StorageKind, StorageSnapshot };
+#[allow(clippy::module_inception)]
 pub mod session;  pub use session::Session;
+pub mod builder;  pub use builder::SessionBuilder;
+pub mod context;  pub use context::{ BrowserContext, ContextOptions };
@@ -1,17 +1,23 @@
 use crate::prelude::*;
+use crate::cdp::{ self, CdpSession };
+use crate::bidi::BiDi;
+use crate::console::ConsoleMessage;
+use crate::emulation::UserAgent;
+use crate::network::BlockRules;
+use crate::permission::{ Permission, PermissionState };
 use super::*;
 
-use std::process::{ Command, Stdio };
 use reqwest::Client;
 use serde_json::{ json, Value };
 
 /// The chromedriver session
 #[derive(Clone)]
 pub struct Session {
-    client: Client,
-    port: u16,
-    session_id: String,
-    manager: Arc<SessionManager>
+    pub(crate) client: Client,
+    pub(crate) port: u16,
+    pub(crate) session_id: String,
+    pub(crate) manager: Arc<SessionManager>,
+    pub(crate) shared: Arc<Shared>,
 }
 
 impl Session {
@@ -24,7 +30,8 @@ impl Session {
             port,
             client,
             session_id: session_id.into(),
-            manager: Arc::new(SessionManager::new())
+            manager: Arc::new(SessionManager::new()),
+            shared: Arc::new(Shared::new(None, None)),
         })
     }
 
@@ -38,115 +45,134 @@ impl Session {
         &self.session_id
     }
     
+    /// Creates launch options for a new chromedriver session
+    /// * port: a new chromedriver session IP-port
+    /// * chromedriver_path: path to chromedriver
+    pub fn builder<P: Into<PathBuf>>(port: u16, chromedriver_path: P) -> SessionBuilder {
+        SessionBuilder::new(port, chromedriver_path)
+    }
+
     /// Run chromedriver session in new window
     /// * port: a new chromedriver session IP-port
     /// * chromedriver_path: path to chromedriver
     /// * profile_path: path to storage user profile (None = do not save session)
     /// * headless: runs as headless mode (without interface)
     pub async fn run<P: Into<PathBuf>>(port: u16, chromedriver_path: P, profile_path: Option<PathBuf>, headless: bool) -> Result<Self> {
-        // get path to chromedriver:
-        let mut cmd = Command::new(chromedriver_path.into());
+        SessionBuilder::new(port, chromedriver_path)
+            .profile(profile_path)
+            .headless(headless)
+            .run()
+            .await
+    }
 
-        // starting chromedriver server as background process:
-        cmd.arg(fmt!("--port={port}"))
-            .arg("--silent")
-            .stdout(Stdio::null())
-            .stderr(Stdio::null());
+    /// Returns the WebDriver endpoint URL of the session
+    pub(crate) fn endpoint(&self, path: &str) -> String {
+        fmt!("{}/{path}", self.session_url())
+    }
 
-        // settings for launching without a terminal window:
-        #[cfg(windows)]
-        {
-            use std::os::windows::process::CommandExt;
-            cmd.creation_flags(0x08000000);
-        }
-        #[cfg(unix)]
-        {
-            use std::os::unix::process::CommandExt;
-            cmd.stdin(Stdio::null());
-        }
+    /// Returns the WebDriver session URL
+    pub(crate) fn session_url(&self) -> String {
+        fmt!("http://127.0.0.1:{}/session/{}", self.port, self.session_id)
+    }
 
-        let _ = cmd.spawn()?;
-        tokio::time::sleep(std::time::Duration::from_secs(1)).await;  // waiting when chromedriver is initializes..
-
-        // init request options:
-        let mut options = json!({
-            "browserName": "chrome"
-        });
-
-        // loading & saving profile data + headless mode:
-        let mut args = vec![];
-        if let Some(path) = profile_path {
-            let path = path
-                .to_str()
-                .ok_or(Error::InvalidPath)?
-                .to_owned();
-
-            args.push(fmt!("--user-data-dir={path}"));
-            args.push(fmt!("--disable-cache"));
-            args.push(fmt!("--disk-cache-size=1"));
-        }
+    /// Sets the W3C session timeouts (unset values are kept)
+    pub async fn set_timeouts(&self, timeouts: Timeouts) -> Result<()> {
+        command::send(&self.client, Method::POST, &self.endpoint("timeouts"), Some(timeouts.to_json())).await?;
+        Ok(())
+    }
 
-        // append headless mode:
-        if headless {
-            args.push("--headless".to_string());
-            args.push("--disable-gpu".to_string());
-        }
-        options["goog:chromeOptions"] = json!({ "args": args });
+    /// Returns the W3C session timeouts
+    pub async fn get_timeouts(&self) -> Result<Timeouts> {
+        let value = command::send(&self.client, Method::GET, &self.endpoint("timeouts"), None).await?;
+        Ok(Timeouts::from_json(&value))
+    }
 
-        // disable automation warning:
-        #[cfg(feature = "no-automation")]
-        {
-            options["goog:chromeOptions"]["excludeSwitches"] = json!(["enable-automation"]);
-            options["goog:chromeOptions"]["useAutomationExtension"] = json!(false);
-            options["goog:chromeOptions"]["args"].as_array_mut().unwrap().extend([
-                json!("--disable-blink-features=AutomationControlled"),
-            ]);
-        }
+    /// Sends the CDP (Chrome DevTools Protocol) command to the current tab without waiting for the result
+    pub async fn send_cdp(&self, method: &str, params: Value) -> Result<()> {
+        cdp::send(&self.client, &self.session_url(), method, params, false).await?;
+        Ok(())
+    }
 
-        // init client:
-        let client = Client::new();
-        let session_url = fmt!("http://127.0.0.1:{port}/session");
-        
-        // send request:
-        let response = client
-            .post(&session_url)
-            .json(&json!({
-                "capabilities": {
-                    "alwaysMatch": options
-                }
-            }))
-            .send()
-            .await?
-            .json::<Value>()
-            .await?;
+    /// Sends the CDP (Chrome DevTools Protocol) command to the current tab and returns the result
+    pub async fn send_cdp_with_result(&self, method: &str, params: Value) -> Result<Value> {
+        cdp::send(&self.client, &self.session_url(), method, params, true).await
+    }
 
-        // get session id:
-        let session_id = response["value"]["sessionId"]
-            .as_str()
-            .ok_or(Error::IncorrectSessionId)?
-            .to_string();
+    /// Returns the browser DevTools address (e.g. '127.0.0.1:9222')
+    pub fn get_debugger_address(&self) -> Option<String> {
+        self.shared.debugger_address()
+    }
 
-        #[allow(unused_mut)]
-        let mut this = Self {
-            client,
-            port,
-            session_id,
-            manager: Arc::new(SessionManager::new()),
-        };
+    /// Sets the browser DevTools address (needed for the session created by 'Session::new')
+    pub fn set_debugger_address<S: Into<String>>(&self, address: S) {
+        self.shared.set_debugger_address(address.into());
+    }
 
-        #[cfg(feature = "no-automation")]
-        {
-            this.disable_automation().await?;
-        }
-            
-        Ok(this)
+    /// Returns the browser-level CDP session over the DevTools WebSocket
+    pub async fn cdp(&self) -> Result<CdpSession> {
+        let connection = self.shared.cdp(&self.client).await?;
+        Ok(CdpSession::new(connection, None))
+    }
+
+    /// Returns the WebDriver BiDi WebSocket URL
+    pub fn get_bidi_url(&self) -> Option<String> {
+        self.shared.bidi_url()
+    }
+
+    /// Sets the WebDriver BiDi WebSocket URL (needed for the session created by 'Session::new')
+    pub fn set_bidi_url<S: Into<String>>(&self, url: S) {
+        self.shared.set_bidi_url(url.into());
+    }
+
+    /// Returns the WebDriver BiDi client (the session must be launched with 'SessionBuilder::bidi')
+    pub async fn bidi(&self) -> Result<BiDi> {
+        self.shared.bidi().await
+    }
+
+    /// Sets the default blocking rules of the tabs opened by 'Session::open' (None = do not block)
+    pub fn set_block_rules(&self, rules: Option<BlockRules>) {
+        self.shared.update_tab_defaults(|defaults| defaults.block_rules = rules);
+    }
+
+    /// Sets whether the console messages of the tabs opened by 'Session::open' are collected from their start
+    pub fn set_collect_console(&self, enable: bool) {
+        self.shared.update_tab_defaults(|defaults| defaults.console = enable);
+    }
+
+    /// Sets the default user agent override of the tabs opened by 'Session::open' (None = do not override)
+    pub fn set_user_agent(&self, user_agent: Option<UserAgent>) {
+        self.shared.update_tab_defaults(|defaults| defaults.user_agent = user_agent);
+    }
+
+    /// Sets the default extra HTTP headers of the tabs opened by 'Session::open'
+    pub fn set_extra_headers<K, V>(&self, headers: impl IntoIterator<Item = (K, V)>)
+    where
+        K: Into<String>,
+        V: Into<String>
+    {
+        let headers = headers.into_iter().map(|(k, v)| (k.into(), v.into())).collect();
+        self.shared.update_tab_defaults(|defaults| defaults.extra_headers = headers);
+    }
+
+    /// Takes the browser log entries collected since the last call (console messages & errors of all tabs),
+    /// note: requires 'SessionBuilder::browser_log'
+    pub async fn drain_browser_log(&self) -> Result<Vec<ConsoleMessage>> {
+        let log = command::send(&self.client, Method::POST, &self.endpoint("se/log"), Some(json!({ "type": "browser" }))).await?;
+
+        Ok(log.as_array()
+            .map(|entries| entries.iter().filter_map(ConsoleMessage::from_browser_log).collect())
+            .unwrap_or_default())
+    }
+
+    /// Executes the typed CDP command on the current tab (e.g. 'cdp::Browser::GetVersion')
+    pub async fn execute<C: cdp::Command>(&self, command: C) -> Result<C::Response> {
+        let result = self.send_cdp_with_result(C::METHOD, cdp::params(&command)?).await?;
+        cdp::response::<C>(result)
     }
 
     /// Disabled automation context
     #[cfg(feature = "no-automation")]
-    async fn disable_automation(&mut self) -> Result<()> {
-        let cdp_url = fmt!("http://127.0.0.1:{}/session/{}/chromium/send_command", self.port, self.session_id);
-    
+    pub(crate) async fn disable_automation(&mut self) -> Result<()> {
         let script = r#"
             Object.defineProperty(navigator, 'webdriver', {
                 get: () => undefined
@@ -156,23 +182,8 @@ impl Session {
                 get: () => [1,2,3,4,5]
             });
         "#;
-        
-        let params = json!({
-            "cmd": "Page.addScriptToEvaluateOnNewDocument",
-            "params": { "source": script }
-        });
 
-        let resp = self.client
-            .post(&cdp_url)
-            .json(&params)
-            .send()
-            .await?
-            .json::<Value>()
-            .await?;
-
-        if resp.get("error").is_some() {
-            return Err(fmt!("CDP command failed: {resp:?}").into());
-        }
+        self.send_cdp("Page.addScriptToEvaluateOnNewDocument", json!({ "source": script })).await?;
 
         let exec_url = fmt!("http://127.0.0.1:{}/session/{}/execute/sync", self.port, self.session_id);
         let _ = self.client
@@ -188,6 +199,19 @@ impl Session {
         Ok(())
     }
 
+    /// Creates the tab handler
+    pub(crate) fn new_tab(&self, tab_id: String) -> Tab {
+        Tab {
+            client: self.client.clone(),
+            port: self.port,
+            session_id: self.session_id.clone(),
+            tab_id,
+            url: String::new(),
+            manager: self.manager.clone(),
+            shared: self.shared.clone(),
+        }
+    }
+
     /// Returns all tab identifiers
     pub async fn get_tabs_ids(&self) -> Result<Vec<String>> {
         let handles_url = fmt!("http://127.0.0.1:{}/session/{}/window/handles", self.port, self.session_id);
@@ -243,15 +267,7 @@ impl Session {
         
         let mut tabs = Vec::with_capacity(handles.len());
         for tab_id in handles {
-            let tab = Tab {
-                client: self.client.clone(),
-                port: self.port,
-                session_id: self.session_id.clone(),
-                tab_id,
-                url: String::new(),
-                manager: self.manager.clone(),
-            };
-            tabs.push(Arc::new(Mutex::new(tab)));
+            tabs.push(Arc::new(Mutex::new(self.new_tab(tab_id))));
         }
         
         Ok(tabs)
@@ -266,14 +282,7 @@ impl Session {
             return Ok(None);
         }
         
-        let tab = Arc::new(Mutex::new(Tab {
-            client: self.client.clone(),
-            port: self.port,
-            session_id: self.session_id.clone(),
-            tab_id,
-            url: String::new(),
-            manager: self.manager.clone(),
-        }));
+        let tab = Arc::new(Mutex::new(self.new_tab(tab_id)));
         
         Ok(Some(tab))
     }
@@ -282,8 +291,8 @@ impl Session {
     pub async fn open<S: Into<String>>(&self, url: S) -> Result<Arc<Mutex<Tab>>> {
         let url = url.into();
 
-        // lock tabs activity:
-        self.manager.lock().await;
+        // lock tabs activity (unlocked on drop, even if opening fails):
+        let guard = self.manager.guard().await;
 
         // activate last tab:
         {
@@ -293,7 +302,7 @@ impl Session {
             // activate last tab:
             let last_handle = handles.last().ok_or(Error::NoWindowHandles)?.clone();
             self.client
-                .post(&fmt!("http://127.0.0.1:{}/session/{}/window", self.port, self.session_id))
+                .post(fmt!("http://127.0.0.1:{}/session/{}/window", self.port, self.session_id))
                 .json(&json!({"handle": &last_handle}))
                 .send()
                 .await?;
@@ -320,29 +329,65 @@ impl Session {
             let new_handle = handles.last().ok_or(Error::NoWindowHandles)?.clone();
 
             // create tab:
-            let mut tab = Tab {
-                client: self.client.clone(),
-                port: self.port,
-                session_id: self.session_id.clone(),
-                tab_id: new_handle,
-                url: str!(""),
-                manager: self.manager.clone()
-            };
+            let mut tab = self.new_tab(new_handle);
 
             // unlock tabs:
-            self.manager.unlock().await;
-            
+            drop(guard);
+
+            // apply the default settings:
+            tab.apply_defaults(&self.shared.tab_defaults()).await?;
+
             // open URL:
             tab.open(url).await?;
             tab
         };
 
-        // unlock tabs (if it's not already unlocked):
-        self.manager.unlock().await;
-
         Ok(Arc::new(Mutex::new(tab)))
     }
 
+    /// Grants the permissions to the origin (e.g. '<https://example.com>') without the browser prompts
+    pub async fn grant_permissions<'a>(&self, origin: &str, permissions: impl IntoIterator<Item = &'a Permission>) -> Result<()> {
+        for permission in permissions {
+            self.set_permission(origin, permission, PermissionState::Granted).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Sets the permission state of the origin (e.g. '<https://example.com>')
+    pub async fn set_permission(&self, origin: &str, permission: &Permission, state: PermissionState) -> Result<()> {
+        self.cdp().await?.execute(cdp::Browser::SetPermission {
+            permission: permission.to_descriptor(),
+            setting: state.to_setting(),
+            origin: Some(origin.to_owned()),
+            embedding_origin: None,
+            browser_context_id: None,
+        }).await?;
+
+        Ok(())
+    }
+
+    /// Resets all permission overrides of the default browser context
+    pub async fn reset_permissions(&self) -> Result<()> {
+        self.cdp().await?.execute(cdp::Browser::ResetPermissions { browser_context_id: None }).await?;
+        Ok(())
+    }
+
+    /// Sets the permission state of the current tab origin via the W3C 'POST /permissions' (no DevTools needed)
+    pub async fn set_current_permission(&self, permission: &Permission, state: PermissionState) -> Result<()> {
+        command::send(&self.client, Method::POST, &self.endpoint("permissions"), Some(json!({
+            "descriptor": permission.to_json(),
+            "state": state.name(),
+        }))).await?;
+
+        Ok(())
+    }
+
+    /// Creates an isolated (incognito-like) browser context with separate cookies & storage
+    pub async fn new_context(&self, options: ContextOptions) -> Result<BrowserContext> {
+        BrowserContext::create(self.clone(), options).await
+    }
+
     /// Close chromedriver session
     pub async fn close(&self) -> Result<()> {
         // close session:
@@ -360,6 +405,9 @@ impl Session {
         let quit_url = fmt!("http://127.0.0.1:{}/quit", self.port);
         let _ = self.client.post(&quit_url).send().await;
 
+        // remove the proxy authentication extension:
+        self.shared.extension_dir.lock().unwrap().take();
+
         Ok(())
     }
 }
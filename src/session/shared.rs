@@ -0,0 +1,138 @@
+use crate::prelude::*;
+use crate::cdp::CdpConnection;
+use crate::bidi::BiDi;
+use crate::console::ConsoleCollector;
+use crate::emulation::{ Geolocation, UserAgent };
+use crate::network::{ BlockRules, HarRecorder, PerformanceEntry, ProxyCredentials, Router };
+
+use std::collections::HashMap;
+use std::sync::RwLock;
+use reqwest::Client;
+use tempfile::TempDir;
+
+/// The settings applied to the launched tabs & the tabs opened by 'Session::open'
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct TabDefaults {
+    pub(crate) block_rules: Option<BlockRules>,
+    pub(crate) user_agent: Option<UserAgent>,
+    pub(crate) extra_headers: Vec<(String, String)>,
+    pub(crate) proxy_credentials: Option<ProxyCredentials>,
+    pub(crate) geolocation: Option<Geolocation>,
+    pub(crate) timezone: Option<String>,
+    pub(crate) locale: Option<String>,
+    /// Collect the console messages from the tab start
+    pub(crate) console: bool,
+}
+
+/// The session state shared between the session and its tabs
+#[derive(Default)]
+pub(crate) struct Shared {
+    /// The browser DevTools address (e.g. '127.0.0.1:9222')
+    debugger_address: RwLock<Option<String>>,
+    /// The DevTools WebSocket connection (opened on first use, reopened if it's closed)
+    cdp: Mutex<Option<Arc<CdpConnection>>>,
+    /// The WebDriver BiDi WebSocket URL
+    bidi_url: RwLock<Option<String>>,
+    /// The WebDriver BiDi connection (opened on first use, reopened if it's closed)
+    bidi: Mutex<Option<BiDi>>,
+    /// The tab request routers (target id -> router)
+    pub(crate) routers: Mutex<HashMap<String, Arc<Router>>>,
+    /// The tab HAR recorders (target id -> recorder)
+    pub(crate) recorders: Mutex<HashMap<String, HarRecorder>>,
+    /// The drained performance log entries not yet taken by their tabs (target id -> entries)
+    pub(crate) performance_log: Mutex<HashMap<String, Vec<PerformanceEntry>>>,
+    /// The tab console collectors (target id -> collector)
+    pub(crate) consoles: Mutex<HashMap<String, ConsoleCollector>>,
+    /// The default settings of new tabs
+    tab_defaults: RwLock<TabDefaults>,
+    /// The private directory of the proxy authentication extension (removed with the session)
+    pub(crate) extension_dir: std::sync::Mutex<Option<TempDir>>,
+}
+
+impl Shared {
+    /// Creates a new shared state
+    pub(crate) fn new(debugger_address: Option<String>, bidi_url: Option<String>) -> Self {
+        Self {
+            debugger_address: RwLock::new(debugger_address),
+            bidi_url: RwLock::new(bidi_url),
+            ..Default::default()
+        }
+    }
+
+    /// Returns the browser DevTools address
+    pub(crate) fn debugger_address(&self) -> Option<String> {
+        self.debugger_address.read().unwrap().clone()
+    }
+
+    /// Sets the browser DevTools address
+    pub(crate) fn set_debugger_address(&self, address: String) {
+        *self.debugger_address.write().unwrap() = Some(address);
+    }
+
+    /// Returns the DevTools WebSocket connection (connects on first use and after the connection is lost)
+    pub(crate) async fn cdp(&self, client: &Client) -> Result<Arc<CdpConnection>> {
+        let mut cdp = self.cdp.lock().await;
+        if let Some(connection) = cdp.as_ref().filter(|connection| !connection.is_closed()) {
+            return Ok(connection.clone());
+        }
+
+        let address = self.debugger_address()
+            .ok_or_else(|| Error::CdpConnectionFailed(str!("the browser debugger address is unknown")))?;
+        let connection = Arc::new(CdpConnection::connect(client, &address).await?);
+
+        *cdp = Some(connection.clone());
+        Ok(connection)
+    }
+
+    /// Returns the DevTools WebSocket connection if it's already opened
+    pub(crate) async fn cdp_opened(&self) -> Option<Arc<CdpConnection>> {
+        self.cdp.lock().await.clone()
+    }
+
+    /// Returns the WebDriver BiDi WebSocket URL
+    pub(crate) fn bidi_url(&self) -> Option<String> {
+        self.bidi_url.read().unwrap().clone()
+    }
+
+    /// Sets the WebDriver BiDi WebSocket URL
+    pub(crate) fn set_bidi_url(&self, url: String) {
+        *self.bidi_url.write().unwrap() = Some(url);
+    }
+
+    /// Returns the WebDriver BiDi connection (connects on first use and after the connection is lost)
+    pub(crate) async fn bidi(&self) -> Result<BiDi> {
+        let mut bidi = self.bidi.lock().await;
+        if let Some(bidi) = bidi.as_ref().filter(|bidi| !bidi.is_closed()) {
+            return Ok(bidi.clone());
+        }
+
+        let url = self.bidi_url()
+            .ok_or_else(|| Error::WebSocketFailed(str!("the BiDi WebSocket URL is unknown (enable 'SessionBuilder::bidi')")))?;
+        let connection = BiDi::connect(&url).await?;
+
+        *bidi = Some(connection.clone());
+        Ok(connection)
+    }
+
+    /// Drops the closed tab state (routers, recorders, DevTools session)
+    pub(crate) async fn forget_tab(&self, target_id: &str) {
+        self.routers.lock().await.remove(target_id);
+        self.recorders.lock().await.remove(target_id);
+        self.performance_log.lock().await.remove(target_id);
+        self.consoles.lock().await.remove(target_id);
+
+        if let Some(connection) = self.cdp_opened().await {
+            connection.detach(target_id).await;
+        }
+    }
+
+    /// Returns the default settings of new tabs
+    pub(crate) fn tab_defaults(&self) -> TabDefaults {
+        self.tab_defaults.read().unwrap().clone()
+    }
+
+    /// Changes the default settings of new tabs
+    pub(crate) fn update_tab_defaults<F: FnOnce(&mut TabDefaults)>(&self, update: F) {
+        update(&mut self.tab_defaults.write().unwrap());
+    }
+}
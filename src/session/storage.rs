@@ -0,0 +1,139 @@
+use crate::prelude::*;
+use super::Tab;
+
+use std::collections::BTreeMap;
+use serde::{ Serialize, Deserialize, de::DeserializeOwned };
+use serde_json::{ json, Value };
+
+/// The web storage area
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StorageKind {
+    Local,
+    Session,
+}
+
+impl StorageKind {
+    /// Returns the JavaScript storage object name
+    fn object(&self) -> &'static str {
+        match self {
+            Self::Local => "localStorage",
+            Self::Session => "sessionStorage",
+        }
+    }
+}
+
+/// The web storage of the tab current origin
+#[derive(Clone)]
+pub struct Storage<'a> {
+    tab: &'a Tab,
+    kind: StorageKind,
+}
+
+impl<'a> Storage<'a> {
+    /// Creates the storage handle of the tab
+    pub fn new(tab: &'a Tab, kind: StorageKind) -> Self {
+        Self { tab, kind }
+    }
+
+    /// Returns the storage area
+    pub fn kind(&self) -> StorageKind {
+        self.kind
+    }
+
+    /// Runs the script with the 'storage' variable, the storage access errors mean the storage is not available
+    /// (e.g. the 'SecurityError' on 'about:blank', 'data:' URLs & other opaque origins), the other errors are kept
+    async fn run<D: DeserializeOwned>(&self, script: &str) -> Result<D> {
+        let result = self.tab.inject::<Value>(&fmt!(
+            "let storage; try {{ storage = window.{}; }} catch (e) {{ return {{ unavailable: String(e && e.message || e) }}; }} return {{ value: (() => {{ {script} }})() }};",
+            self.kind.object()
+        )).await?;
+
+        if let Some(message) = result.get("unavailable") {
+            return Err(Error::StorageUnavailable(message.as_str().unwrap_or_default().to_owned()).into());
+        }
+
+        Ok(serde_json::from_value(result.get("value").cloned().unwrap_or(Value::Null))?)
+    }
+
+    /// Returns the item value
+    pub async fn get(&self, key: &str) -> Result<Option<String>> {
+        self.run(&fmt!("return storage.getItem({});", json!(key))).await
+    }
+
+    /// Sets the item value
+    pub async fn set(&self, key: &str, value: &str) -> Result<()> {
+        self.run(&fmt!("storage.setItem({}, {});", json!(key), json!(value))).await
+    }
+
+    /// Removes the item
+    pub async fn remove(&self, key: &str) -> Result<()> {
+        self.run(&fmt!("storage.removeItem({});", json!(key))).await
+    }
+
+    /// Removes all items
+    pub async fn clear(&self) -> Result<()> {
+        self.run("storage.clear();").await
+    }
+
+    /// Returns the item keys
+    pub async fn keys(&self) -> Result<Vec<String>> {
+        self.run("return Object.keys(storage);").await
+    }
+
+    /// Returns all items
+    pub async fn items(&self) -> Result<BTreeMap<String, String>> {
+        self.run("return Object.assign({}, storage);").await
+    }
+
+    /// Writes the items (keeps the other items)
+    pub async fn set_items(&self, items: &BTreeMap<String, String>) -> Result<()> {
+        self.run(&fmt!("const items = {}; for (const key in items) storage.setItem(key, items[key]);", json!(items))).await
+    }
+}
+
+/// The web storage snapshot of the origin (persisted as JSON alongside the cookies)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct StorageSnapshot {
+    /// The origin (e.g. '<https://example.com>')
+    pub origin: String,
+    pub local_storage: BTreeMap<String, String>,
+    pub session_storage: BTreeMap<String, String>,
+}
+
+impl StorageSnapshot {
+    /// Parses the snapshot from the JSON string
+    pub fn from_json(json: &str) -> Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Serializes the snapshot to the JSON string
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Takes the snapshot of the tab current origin
+    pub(crate) async fn take(tab: &Tab) -> Result<Self> {
+        Ok(Self {
+            origin: tab.inject("return window.location.origin;").await?,
+            local_storage: tab.local_storage().items().await?,
+            session_storage: tab.session_storage().items().await?,
+        })
+    }
+
+    /// Restores the snapshot into the tab, replacing the current items (the tab must be on the snapshot origin)
+    pub(crate) async fn restore(&self, tab: &Tab) -> Result<()> {
+        let origin = tab.inject::<String>("return window.location.origin;").await?;
+        if origin != self.origin {
+            return Err(Error::StorageOriginMismatch(self.origin.clone()).into());
+        }
+
+        // the items added after the snapshot are removed too:
+        for (storage, items) in [(tab.local_storage(), &self.local_storage), (tab.session_storage(), &self.session_storage)] {
+            storage.clear().await?;
+            storage.set_items(items).await?;
+        }
+
+        Ok(())
+    }
+}
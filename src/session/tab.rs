@@ -1,9 +1,31 @@
 use crate::prelude::*;
-use super::SessionManager;
+use crate::cdp::{ self, CdpSession, EventStream };
+use crate::console::{ self, ConsoleCollector, ConsoleMessage };
+use crate::emulation::{ Device, Geolocation, NetworkConditions, UserAgent };
+use crate::input::Actions;
+use crate::permission::{ Permission, PermissionState };
+use crate::network::{ self, BlockRules, BlockStats, CapturedResponse, Har, HarRecorder, PerformanceEntry, ProxyCredentials, ReplayOptions, RouteAction, RouteKind, Router };
+use crate::vision::{ self, GrayImage, ImageMatch };
+use super::{ command, PageWatcher, SessionManager, Shared, Storage, StorageKind, StorageSnapshot, TabDefaults, WaitUntil };
+
+use std::future::Future;
+use base64::{ Engine, engine::general_purpose::STANDARD as BASE64 };
+use futures::StreamExt;
 
 use reqwest::Client;
 use serde_json::{ json, Value };
 
+/// The W3C web element identifier key
+pub(crate) const ELEMENT_KEY: &str = "element-6066-11e4-a23c-4e5a8d1e1d4b";
+
+/// The page condition polling interval
+const WAIT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Converts the window handle to the DevTools target id (old chromedriver versions prefix the handles with 'CDwindow-')
+pub(crate) fn target_id(handle: &str) -> &str {
+    handle.strip_prefix("CDwindow-").unwrap_or(handle)
+}
+
 // The window tab
 #[derive(Clone)]
 pub struct Tab {
@@ -12,7 +34,8 @@ pub struct Tab {
     pub(crate) session_id: String,
     pub(crate) tab_id: String,
     pub(crate) url: String,
-    pub(crate) manager: Arc<SessionManager>
+    pub(crate) manager: Arc<SessionManager>,
+    pub(crate) shared: Arc<Shared>,
 }
 
 impl Tab {
@@ -26,10 +49,57 @@ impl Tab {
         &self.tab_id
     }
     
+    /// Returns the DevTools target id of the tab
+    pub fn get_target_id(&self) -> &str {
+        target_id(&self.tab_id)
+    }
+
+    /// Returns the WebDriver endpoint URL of the tab session
+    pub(crate) fn endpoint(&self, path: &str) -> String {
+        fmt!("{}/{path}", self.session_url())
+    }
+
+    /// Returns the WebDriver session URL
+    pub(crate) fn session_url(&self) -> String {
+        fmt!("http://127.0.0.1:{}/session/{}", self.port, self.session_id)
+    }
+
+    /// Sends the WebDriver command and returns the response value
+    pub(crate) async fn command(&self, method: Method, path: &str, body: Option<Value>) -> Result<Value> {
+        command::send(&self.client, method, &self.endpoint(path), body).await
+    }
+
+    /// Runs a task on the active tab, locking other tasks until it's done
+    pub(crate) async fn locked<T, F>(&self, task: F) -> Result<T>
+    where
+        F: Future<Output = Result<T>>
+    {
+        // lock other tasks (unlocked on drop, even if the task fails):
+        let _guard = self.manager.guard().await;
+
+        // do tab active & run task:
+        self.active_without_lock().await?;
+        task.await
+    }
+
+    /// Finds the element by CSS selector and returns its W3C reference
+    pub(crate) async fn find_element(&self, selector: &str) -> Result<Value> {
+        let value = self.command(Method::POST, "element", Some(json!({
+            "using": "css selector",
+            "value": selector
+        }))).await.map_err(|_| Error::ElementNotFound)?;
+
+        if value.get(ELEMENT_KEY).is_none() {
+            return Err(Error::ElementNotFound.into());
+        }
+
+        Ok(value)
+    }
+
     /// Do tab active without locking other tasks
     async fn active_without_lock(&self) -> Result<()> {
         self.client
-            .post(&fmt!("http://127.0.0.1:{}/session/{}/window", self.port, self.session_id))
+            .post(fmt!("http://127.0.0.1:{}/session/{}/window", self.port, self.session_id))
             .json(&json!({"handle": self.tab_id }))
             .send()
             .await?;
@@ -39,16 +109,7 @@ impl Tab {
 
     /// Do tab active
     pub async fn active(&self) -> Result<()> {
-        // lock other tasks:
-        self.manager.lock().await;
-        
-        // do tab active:
-        self.active_without_lock().await?;
-
-        // unlock other tasks:
-        self.manager.unlock().await;
-
-        Ok(())
+        self.locked(async { Ok(()) }).await
     }
     
     /// Open URL-address
@@ -58,63 +119,543 @@ impl Tab {
     {
         let url = url.into();
 
-        // lock other tasks:
-        self.manager.lock().await;
-
-        // do tab active:
-        self.active_without_lock().await?;
-
-        // loading URL:
-        self.client
-            .post(&fmt!("http://127.0.0.1:{}/session/{}/url", self.port, self.session_id))
-            .json(&json!({ "url": url }))
-            .send()
-            .await?;
+        // loading URL (the page load timeout is returned as an error):
+        self.locked(self.command(Method::POST, "url", Some(json!({ "url": &url })))).await?;
 
         // update url:
         self.url = url;
 
-        // unlock other tasks:
-        self.manager.unlock().await;
-
         Ok(())
     }
 
+    /// Open URL-address and wait until the page is ready
+    pub async fn open_with<S>(&mut self, url: S, wait: WaitUntil, timeout: Duration) -> Result<()>
+    where
+        S: Into<String>
+    {
+        // watch before the navigation to not miss its requests & to not check the previous document
+        // (with the 'none' page load strategy):
+        let watcher = self.watch(true).await.ok();
+
+        self.open(url).await?;
+        self.wait_with(wait, timeout, watcher).await
+    }
+
+    /// Waits until the page condition is met
+    pub async fn wait_until(&self, wait: WaitUntil, timeout: Duration) -> Result<()> {
+        let watcher = match wait {
+            WaitUntil::NetworkIdle(_) => self.watch(false).await.ok(),
+            _ => None,
+        };
+
+        self.wait_with(wait, timeout, watcher).await
+    }
+
+    /// Starts watching the tab navigation & network activity
+    async fn watch(&self, navigation: bool) -> Result<PageWatcher> {
+        PageWatcher::start(&self.cdp().await?, self.get_target_id(), navigation).await
+    }
+
+    /// Checks the page condition script, the script errors (e.g. while the previous document unloads) mean 'not ready yet'
+    async fn check<D: serde::de::DeserializeOwned>(&self, script: &str) -> Result<Option<D>> {
+        match self.inject::<D>(script).await {
+            Ok(value) => Ok(Some(value)),
+            Err(e) if matches!(e.downcast_ref::<Error>(), Some(Error::CommandFailed(_))) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Waits until the page condition is met (the network idle is counted by resources without the watcher)
+    async fn wait_with(&self, wait: WaitUntil, timeout: Duration, mut watcher: Option<PageWatcher>) -> Result<()> {
+        let script = wait.script();
+        let deadline = tokio::time::Instant::now() + timeout;
+
+        // the last resources count & the time it was changed (for network idle without the watcher):
+        let mut resources = (-1i64, tokio::time::Instant::now());
+
+        loop {
+            if let Some(watcher) = &mut watcher {
+                watcher.poll();
+            }
+
+            // some events are lost, so fall back to the document state:
+            if watcher.as_ref().is_some_and(|watcher| watcher.lagged()) {
+                watcher = None;
+            }
+
+            if watcher.as_ref().is_none_or(|watcher| watcher.navigated()) {
+                match (&wait, &watcher) {
+                    (WaitUntil::NetworkIdle(idle), Some(watcher)) => {
+                        let parsed = self.check::<bool>(&WaitUntil::DomContentLoaded.script()).await?.unwrap_or(false);
+                        if parsed && watcher.idle_for().is_some_and(|time| time >= *idle) {
+                            return Ok(());
+                        }
+                    },
+                    (WaitUntil::NetworkIdle(idle), None) => {
+                        let count = self.check::<i64>(&script).await?.unwrap_or(-1);
+                        let now = tokio::time::Instant::now();
+
+                        if count < 0 || count != resources.0 {
+                            resources = (count, now);
+                        } else if now - resources.1 >= *idle {
+                            return Ok(());
+                        }
+                    },
+                    _ => if self.check::<bool>(&script).await?.unwrap_or(false) {
+                        return Ok(());
+                    }
+                }
+            }
+
+            if tokio::time::Instant::now() >= deadline {
+                return Err(Error::WaitTimeout.into());
+            }
+            sleep(WAIT_POLL_INTERVAL).await;
+        }
+    }
+
     /// Inject JavaScript to window tab
     pub async fn inject<D: serde::de::DeserializeOwned>(&self, script: &str) -> Result<D> {
-        // lock other tasks:
-        self.manager.lock().await;
-        
-        // do tab active:
-        self.active_without_lock().await?;
-
         // execute script:
-        let url = fmt!("http://127.0.0.1:{}/session/{}/execute/sync", self.port, self.session_id);
-        let response = self.client
-            .post(&url)
-            .json(&json!({
-                "script": script,
-                "args": []
-            }))
-            .send()
-            .await?
-            .json::<Value>()
-            .await?;
-
-        // unlock other tasks:
-        self.manager.unlock().await;
+        let value = self.locked(self.command(Method::POST, "execute/sync", Some(json!({
+            "script": script,
+            "args": []
+        })))).await?;
 
-        let value = response.get("value")
-            .ok_or(Error::UnexpectedResponse)?
-            .to_owned();
-        
         Ok(serde_json::from_value::<D>(value)?)
     }
 
+    /// Sends the CDP (Chrome DevTools Protocol) command to the tab without waiting for the result
+    pub async fn send_cdp(&self, method: &str, params: Value) -> Result<()> {
+        self.locked(async {
+            cdp::send(&self.client, &self.session_url(), method, params, false).await?;
+            Ok(())
+        }).await
+    }
+
+    /// Sends the CDP (Chrome DevTools Protocol) command to the tab and returns the result
+    pub async fn send_cdp_with_result(&self, method: &str, params: Value) -> Result<Value> {
+        self.locked(async {
+            cdp::send(&self.client, &self.session_url(), method, params, true).await
+        }).await
+    }
+
+    /// Executes the typed CDP command on the tab (e.g. 'cdp::Page::Reload')
+    pub async fn execute<C: cdp::Command>(&self, command: C) -> Result<C::Response> {
+        let result = self.send_cdp_with_result(C::METHOD, cdp::params(&command)?).await?;
+        cdp::response::<C>(result)
+    }
+
+    /// Returns the CDP session attached to the tab over the DevTools WebSocket
+    pub async fn cdp(&self) -> Result<CdpSession> {
+        let connection = self.shared.cdp(&self.client).await?;
+        let session_id = connection.attach(self.get_target_id()).await?;
+
+        Ok(CdpSession::new(connection, Some(session_id)))
+    }
+
+    /// Returns the stream of typed tab events (e.g. 'tab.events::<cdp::Network::ResponseReceived>()')
+    pub async fn events<E: cdp::Event + Send + 'static>(&self) -> Result<EventStream<E>> {
+        self.cdp().await?.events::<E>().await
+    }
+
+    /// Returns the tab requests router (created on first use and restarted after the connection is lost)
+    pub(crate) async fn router(&self) -> Result<Arc<Router>> {
+        let mut routers = self.shared.routers.lock().await;
+        let previous = routers.get(self.get_target_id());
+        if let Some(router) = previous.filter(|router| !router.is_stopped()) {
+            return Ok(router.clone());
+        }
+
+        let router = Router::new(self.cdp().await?, previous.map(|router| router.as_ref())).await?;
+        routers.insert(self.get_target_id().to_owned(), router.clone());
+
+        Ok(router)
+    }
+
+    /// Intercepts the requests matching the URL glob pattern (e.g. '*://*/api/*') and handles them,
+    /// the last registered route is checked first (the blocked requests never reach the routes)
+    pub async fn route<S, F>(&self, pattern: S, handler: F) -> Result<()>
+    where
+        S: Into<String>,
+        F: Fn(&cdp::Fetch::RequestPaused) -> RouteAction + Send + Sync + 'static
+    {
+        self.router().await?.add(pattern.into(), None, Arc::new(handler)).await
+    }
+
+    /// Removes the routes with the URL pattern
+    pub async fn unroute(&self, pattern: &str) -> Result<()> {
+        self.router().await?.remove(pattern).await
+    }
+
+    /// Removes all routes registered by 'Tab::route' (the blocking rules are kept)
+    pub async fn unroute_all(&self) -> Result<()> {
+        self.router().await?.clear().await
+    }
+
+    /// Blocks the requests by resource types & URL patterns (replaces the previous blocking rules)
+    pub async fn block(&self, rules: &BlockRules) -> Result<()> {
+        self.router().await?.block(rules).await
+    }
+
+    /// Returns the blocked requests statistics
+    pub async fn block_stats(&self) -> BlockStats {
+        let routers = self.shared.routers.lock().await;
+        routers.get(self.get_target_id())
+            .map(|router| router.stats())
+            .unwrap_or_default()
+    }
+
+    /// Starts recording the tab network traffic (restarts the current recording)
+    /// * with_bodies: record the response bodies too
+    pub async fn start_har(&self, with_bodies: bool) -> Result<()> {
+        let recorder = HarRecorder::start(self.cdp().await?, with_bodies).await?;
+        self.shared.recorders.lock().await.insert(self.get_target_id().to_owned(), recorder);
+
+        Ok(())
+    }
+
+    /// Stops recording the tab network traffic and returns the HAR 1.2 archive
+    pub async fn stop_har(&self) -> Result<Har> {
+        let recorder = self.shared.recorders.lock().await
+            .remove(self.get_target_id())
+            .ok_or(Error::HarNotRecording)?;
+
+        Ok(recorder.stop().await)
+    }
+
+    /// Answers the tab requests from the HAR archive (replaces the current replay),
+    /// note: the responses recorded without body are handled by the 'Unmatched' policy
+    pub async fn replay_har(&self, har: &Har, options: ReplayOptions) -> Result<()> {
+        let handler = network::replay::handler(har, options);
+        self.router().await?.replace(RouteKind::Replay, vec![(str!("*"), None, handler)]).await
+    }
+
+    /// Stops answering the tab requests from the HAR archive
+    pub async fn stop_replay(&self) -> Result<()> {
+        self.router().await?.replace(RouteKind::Replay, vec![]).await
+    }
+
+    /// Returns the stream of responses (with bodies) matching the URL glob pattern (e.g. '*/api/*')
+    pub async fn responses<S: Into<String>>(&self, url_pattern: S) -> Result<EventStream<CapturedResponse>> {
+        network::response::responses(self.cdp().await?, url_pattern.into()).await
+    }
+
+    /// Waits for the response matching the URL glob pattern and returns it with the body,
+    /// note: only responses finished after the call are captured (use 'Tab::responses' to subscribe in advance)
+    pub async fn wait_for_response<S: Into<String>>(&self, url_pattern: S, timeout: Duration) -> Result<CapturedResponse> {
+        let mut responses = self.responses(url_pattern).await?;
+
+        match tokio::time::timeout(timeout, responses.next()).await {
+            Ok(Some(response)) => Ok(response),
+            Ok(None) => Err(Error::WebSocketFailed(str!("the connection is closed")).into()),
+            Err(_) => Err(Error::WaitTimeout.into()),
+        }
+    }
+
+    /// Takes the tab performance log entries collected since the last call (without DevTools WebSocket),
+    /// note: requires 'SessionBuilder::performance_log'
+    pub async fn drain_performance_log(&self) -> Result<Vec<PerformanceEntry>> {
+        // the log is shared by all tabs, so other tabs entries are kept for them:
+        let mut buffer = self.shared.performance_log.lock().await;
+
+        let log = self.command(Method::POST, "se/log", Some(json!({ "type": "performance" }))).await?;
+        for (target_id, entry) in network::performance::parse_log(&log) {
+            buffer.entry(target_id).or_default().push(entry);
+        }
+
+        Ok(buffer.remove(self.get_target_id()).unwrap_or_default())
+    }
+
+    /// Starts collecting the tab console messages & JavaScript errors (does nothing if it's already started,
+    /// restarts the collecting stopped with the lost connection)
+    pub async fn start_console(&self) -> Result<()> {
+        let mut consoles = self.shared.consoles.lock().await;
+        let previous = consoles.get(self.get_target_id());
+        if previous.is_none_or(|collector| collector.is_stopped()) {
+            let collector = ConsoleCollector::start(&self.cdp().await?, previous).await?;
+            consoles.insert(self.get_target_id().to_owned(), collector);
+        }
+
+        Ok(())
+    }
+
+    /// Returns the console messages & JavaScript errors collected since 'Tab::start_console',
+    /// note: the collecting is started by the first call, so the earlier messages are missed
+    /// (see 'SessionBuilder::collect_console' to collect them from the tab start)
+    pub async fn console_messages(&self) -> Result<Vec<ConsoleMessage>> {
+        self.start_console().await?;
+
+        let consoles = self.shared.consoles.lock().await;
+        Ok(consoles.get(self.get_target_id()).map(|c| c.messages()).unwrap_or_default())
+    }
+
+    /// Returns the collected console errors & uncaught exceptions
+    pub async fn js_errors(&self) -> Result<Vec<ConsoleMessage>> {
+        Ok(self.console_messages().await?
+            .into_iter()
+            .filter(|message| message.is_error())
+            .collect())
+    }
+
+    /// Clears the collected console messages
+    pub async fn clear_console(&self) {
+        if let Some(collector) = self.shared.consoles.lock().await.get(self.get_target_id()) {
+            collector.clear();
+        }
+    }
+
+    /// Returns the live stream of the tab console messages & JavaScript errors
+    pub async fn console(&self) -> Result<EventStream<ConsoleMessage>> {
+        console::messages(&self.cdp().await?).await
+    }
+
+    /// Sets the extra HTTP headers sent with every tab request (replaces the previous ones)
+    pub async fn set_extra_headers<K, V>(&self, headers: impl IntoIterator<Item = (K, V)>) -> Result<()>
+    where
+        K: Into<String>,
+        V: Into<String>
+    {
+        let headers = headers.into_iter()
+            .map(|(k, v)| (k.into(), Value::String(v.into())))
+            .collect::<serde_json::Map<_, _>>();
+
+        let cdp = self.cdp().await?;
+        cdp.enable("Network").await?;
+        cdp.execute(cdp::Network::SetExtraHTTPHeaders { headers: Value::Object(headers) }).await?;
+
+        Ok(())
+    }
+
+    /// Overrides the tab user agent, accepted languages, platform & client hints
+    pub async fn set_user_agent(&self, user_agent: &UserAgent) -> Result<()> {
+        self.cdp().await?.execute(user_agent.to_command()).await?;
+        Ok(())
+    }
+
+    /// Emulates the device viewport, pixel ratio, touch screen & user agent (e.g. 'Device::preset("Pixel 7")'),
+    /// the devices without user agent get the session default one (the previous device user agent is not kept)
+    pub async fn emulate_device(&self, device: &Device) -> Result<()> {
+        let cdp = self.cdp().await?;
+
+        cdp.execute(device.to_metrics()).await?;
+        cdp.execute(cdp::Emulation::SetTouchEmulationEnabled {
+            enabled: device.touch,
+            max_touch_points: device.touch.then_some(5),
+        }).await?;
+
+        match (&device.user_agent, self.shared.tab_defaults().user_agent) {
+            (Some(user_agent), _) => self.set_user_agent(&UserAgent::new(user_agent.clone())).await?,
+            (None, Some(user_agent)) => self.set_user_agent(&user_agent).await?,
+            // the empty user agent disables the override:
+            (None, None) => { cdp.execute(cdp::Emulation::SetUserAgentOverride { user_agent: String::new(), ..Default::default() }).await?; },
+        }
+
+        Ok(())
+    }
+
+    /// Clears the device emulation (the user agent is reset too)
+    pub async fn clear_device(&self) -> Result<()> {
+        let cdp = self.cdp().await?;
+
+        cdp.execute(cdp::Emulation::ClearDeviceMetricsOverride {}).await?;
+        cdp.execute(cdp::Emulation::SetTouchEmulationEnabled { enabled: false, max_touch_points: None }).await?;
+        // the empty user agent disables the override:
+        cdp.execute(cdp::Emulation::SetUserAgentOverride { user_agent: String::new(), ..Default::default() }).await?;
+
+        Ok(())
+    }
+
+    /// Returns the tab browser context id
+    pub(crate) async fn browser_context_id(&self) -> Result<Option<String>> {
+        let browser = CdpSession::new(self.shared.cdp(&self.client).await?, None);
+        let result = browser.send("Target.getTargetInfo", json!({ "targetId": self.get_target_id() })).await?;
+
+        Ok(result["targetInfo"]["browserContextId"].as_str().map(|id| id.to_owned()))
+    }
+
+    /// Emulates the geolocation (the permission is granted automatically in the tab browser context)
+    pub async fn set_geolocation(&self, latitude: f64, longitude: f64, accuracy: f64) -> Result<()> {
+        // note: 'Browser.grantPermissions' would reset the other permissions, so only this one is set:
+        let browser = CdpSession::new(self.shared.cdp(&self.client).await?, None);
+        browser.execute(cdp::Browser::SetPermission {
+            permission: Permission::Geolocation.to_descriptor(),
+            setting: PermissionState::Granted.to_setting(),
+            origin: None,
+            embedding_origin: None,
+            browser_context_id: self.browser_context_id().await?,
+        }).await?;
+
+        self.cdp().await?.execute(Geolocation::new(latitude, longitude, accuracy).to_command()).await?;
+        Ok(())
+    }
+
+    /// Clears the geolocation emulation
+    pub async fn clear_geolocation(&self) -> Result<()> {
+        self.cdp().await?.execute(cdp::Emulation::ClearGeolocationOverride {}).await?;
+        Ok(())
+    }
+
+    /// Emulates the timezone (IANA id, e.g. 'Europe/Berlin')
+    pub async fn set_timezone(&self, timezone: &str) -> Result<()> {
+        self.cdp().await?.execute(cdp::Emulation::SetTimezoneOverride { timezone_id: timezone.to_owned() }).await?;
+        Ok(())
+    }
+
+    /// Emulates the locale (BCP 47 tag, e.g. 'de-DE') for 'Intl' & dates formatting,
+    /// note: the 'Accept-Language' header is set by 'UserAgent::accept_language'
+    pub async fn set_locale(&self, locale: &str) -> Result<()> {
+        self.cdp().await?.execute(cdp::Emulation::SetLocaleOverride { locale: Some(locale.to_owned()) }).await?;
+        Ok(())
+    }
+
+    /// Emulates the network conditions (e.g. 'NetworkConditions::slow_3g()', 'NetworkConditions::none()' disables it)
+    pub async fn emulate_network(&self, conditions: NetworkConditions) -> Result<()> {
+        let cdp = self.cdp().await?;
+        cdp.enable("Network").await?;
+        cdp.execute(conditions.to_command()).await?;
+
+        Ok(())
+    }
+
+    /// Slows down the CPU by the rate (1.0 = no throttling, 4.0 = 4x slower)
+    pub async fn set_cpu_throttling(&self, rate: f64) -> Result<()> {
+        self.cdp().await?.execute(cdp::Emulation::SetCPUThrottlingRate { rate }).await?;
+        Ok(())
+    }
+
+    /// Emulates the CSS media type ('screen', 'print', None = no override) and the media features,
+    /// e.g. [("prefers-color-scheme", "dark"), ("prefers-reduced-motion", "reduce"), ("forced-colors", "active")]
+    pub async fn emulate_media<K, V>(&self, media_type: Option<&str>, features: impl IntoIterator<Item = (K, V)>) -> Result<()>
+    where
+        K: Into<String>,
+        V: Into<String>
+    {
+        let features = features.into_iter()
+            .map(|(name, value)| cdp::Emulation::MediaFeature { name: name.into(), value: value.into() })
+            .collect();
+
+        self.cdp().await?.execute(cdp::Emulation::SetEmulatedMedia {
+            // note: the empty values disable the overrides:
+            media: Some(media_type.unwrap_or_default().to_owned()),
+            features: Some(features),
+        }).await?;
+
+        Ok(())
+    }
+
+    /// Clears the media type & features emulation
+    pub async fn clear_media(&self) -> Result<()> {
+        self.emulate_media(None, Vec::<(String, String)>::new()).await
+    }
+
+    /// Answers the proxy auth challenges of the tab requests with the credentials (None = stop answering)
+    pub async fn set_proxy_credentials(&self, credentials: Option<ProxyCredentials>) -> Result<()> {
+        self.router().await?.set_credentials(credentials).await
+    }
+
+    /// Applies the session default settings
+    pub(crate) async fn apply_defaults(&self, defaults: &TabDefaults) -> Result<()> {
+        if let Some(rules) = &defaults.block_rules {
+            self.block(rules).await?;
+        }
+        if let Some(user_agent) = &defaults.user_agent {
+            self.set_user_agent(user_agent).await?;
+        }
+        if !defaults.extra_headers.is_empty() {
+            self.set_extra_headers(defaults.extra_headers.clone()).await?;
+        }
+        if defaults.proxy_credentials.is_some() {
+            self.set_proxy_credentials(defaults.proxy_credentials.clone()).await?;
+        }
+        if let Some(geo) = defaults.geolocation {
+            self.set_geolocation(geo.latitude, geo.longitude, geo.accuracy).await?;
+        }
+        if let Some(timezone) = &defaults.timezone {
+            self.set_timezone(timezone).await?;
+        }
+        if let Some(locale) = &defaults.locale {
+            self.set_locale(locale).await?;
+        }
+        if defaults.console {
+            self.start_console().await?;
+        }
+
+        Ok(())
+    }
+
+    /// Creates a new W3C actions sequence (keyboard, mouse, pen and wheel input)
+    pub fn actions(&self) -> Actions<'_> {
+        Actions::new(self)
+    }
+
+    /// Returns the 'localStorage' of the current origin
+    pub fn local_storage(&self) -> Storage<'_> {
+        Storage::new(self, StorageKind::Local)
+    }
+
+    /// Returns the 'sessionStorage' of the current origin
+    pub fn session_storage(&self) -> Storage<'_> {
+        Storage::new(self, StorageKind::Session)
+    }
+
+    /// Takes the local & session storage snapshot of the current origin
+    pub async fn storage_snapshot(&self) -> Result<StorageSnapshot> {
+        StorageSnapshot::take(self).await
+    }
+
+    /// Restores the storage snapshot, replacing the current items (the tab must be on the snapshot origin, reload the page to apply it)
+    pub async fn restore_storage(&self, snapshot: &StorageSnapshot) -> Result<()> {
+        snapshot.restore(self).await
+    }
+
+    /// Releases all pressed keys and pointer buttons
+    pub async fn release_actions(&self) -> Result<()> {
+        self.locked(async {
+            self.command(Method::DELETE, "actions", None).await?;
+            Ok(())
+        }).await
+    }
+
+    /// Clicks the left mouse button at the viewport coordinates
+    pub async fn click_at(&self, x: i64, y: i64) -> Result<()> {
+        self.actions()
+            .move_to(x, y)
+            .click()
+            .perform()
+            .await
+    }
+
+    /// Takes a screenshot of the visible viewport (PNG bytes)
+    pub async fn screenshot(&self) -> Result<Vec<u8>> {
+        let data = self.locked(async {
+            self.command(Method::GET, "screenshot", None).await
+        }).await?;
+
+        let data = data.as_str().ok_or(Error::UnexpectedResponse)?;
+        Ok(BASE64.decode(data)?)
+    }
+
+    /// Searches the template image (PNG bytes) on the screenshot and returns the best match
+    /// with a score above the threshold (0.0 - 1.0) in viewport coordinates,
+    /// note: the template must be in device pixels (cut from a screenshot), the match is divided by the device pixel ratio
+    pub async fn find_image(&self, template_png: &[u8], threshold: f64) -> Result<Option<ImageMatch>> {
+        let template = GrayImage::from_png(template_png)?;
+        let screenshot = GrayImage::from_png(&self.screenshot().await?)?;
+        let ratio = self.inject::<f64>("return window.devicePixelRatio || 1;").await?;
+
+        // matching is CPU heavy, so run it outside of the async runtime:
+        let found = tokio::task::spawn_blocking(move || vision::find_template(&screenshot, &template)).await?;
+
+        Ok(found
+            .filter(|m| m.score >= threshold)
+            .map(|m| ImageMatch { rect: m.rect.scale(1.0 / ratio), score: m.score }))
+    }
+
     /// Close window tab
     pub async fn close(&self) -> Result<()> {
-        // lock other tasks:
-        self.manager.lock().await;
+        // lock other tasks (unlocked on drop, even if closing fails):
+        let guard = self.manager.guard().await;
 
         // do tab active:
         self.active_without_lock().await?;
@@ -122,13 +663,13 @@ impl Tab {
         // close tab:
         loop {
             // do tab active (if tab not exists - break):
-            if let Err(_) = self.active_without_lock().await {
+            if self.active_without_lock().await.is_err() {
                 break;
             }
 
             // close tab:
             self.client
-                .delete(&fmt!("http://127.0.0.1:{}/session/{}/window", self.port, self.session_id))
+                .delete(fmt!("http://127.0.0.1:{}/session/{}/window", self.port, self.session_id))
                 .send()
                 .await?;
 
@@ -142,7 +683,10 @@ impl Tab {
         }
 
         // unlock other tasks:
-        self.manager.unlock().await;
+        drop(guard);
+
+        // forget the DevTools session:
+        self.shared.forget_tab(self.get_target_id()).await;
 
         Ok(())
     }
@@ -0,0 +1,94 @@
+use crate::prelude::*;
+
+use serde_json::{ json, Map, Value };
+
+/// The W3C session timeouts (None = keep the driver default)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Timeouts {
+    /// The script evaluation timeout
+    pub script: Option<Duration>,
+    /// The page loading timeout
+    pub page_load: Option<Duration>,
+    /// The implicit element location timeout
+    pub implicit: Option<Duration>,
+}
+
+impl Timeouts {
+    /// Creates empty timeouts
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the script evaluation timeout
+    pub fn script(mut self, timeout: Duration) -> Self {
+        self.script = Some(timeout);
+        self
+    }
+
+    /// Sets the page loading timeout
+    pub fn page_load(mut self, timeout: Duration) -> Self {
+        self.page_load = Some(timeout);
+        self
+    }
+
+    /// Sets the implicit element location timeout
+    pub fn implicit(mut self, timeout: Duration) -> Self {
+        self.implicit = Some(timeout);
+        self
+    }
+
+    /// Returns true if no timeouts are set
+    pub fn is_empty(&self) -> bool {
+        self.script.is_none() && self.page_load.is_none() && self.implicit.is_none()
+    }
+
+    /// Converts timeouts to the W3C JSON object
+    pub(crate) fn to_json(self) -> Value {
+        let mut map = Map::new();
+        for (key, timeout) in [("script", self.script), ("pageLoad", self.page_load), ("implicit", self.implicit)] {
+            if let Some(timeout) = timeout {
+                map.insert(key.to_owned(), json!(timeout.as_millis() as u64));
+            }
+        }
+
+        Value::Object(map)
+    }
+
+    /// Parses timeouts from the W3C JSON object
+    pub(crate) fn from_json(value: &Value) -> Self {
+        let get = |key: &str| value[key].as_u64().map(Duration::from_millis);
+
+        Self {
+            script: get("script"),
+            page_load: get("pageLoad"),
+            implicit: get("implicit"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_to_json_and_back() {
+        let timeouts = Timeouts::new()
+            .page_load(Duration::from_secs(20))
+            .implicit(Duration::from_millis(0));
+
+        let value = timeouts.to_json();
+        assert_eq!(value, json!({ "pageLoad": 20000, "implicit": 0 }));
+        assert_eq!(Timeouts::from_json(&value), timeouts);
+    }
+
+    #[test]
+    fn parses_null_script_timeout() {
+        // the driver reports the unlimited script timeout as 'null':
+        let timeouts = Timeouts::from_json(&json!({ "script": null, "pageLoad": 300000, "implicit": 0 }));
+
+        assert_eq!(timeouts.script, None);
+        assert_eq!(timeouts.page_load, Some(Duration::from_secs(300)));
+        assert_eq!(timeouts.implicit, Some(Duration::ZERO));
+        assert!(!timeouts.is_empty());
+    }
+}
@@ -0,0 +1,143 @@
+use crate::prelude::*;
+use crate::cdp::{ CdpSession, EventStream, RawEvent };
+
+use std::collections::HashSet;
+use futures::{ FutureExt, StreamExt };
+use tokio::time::Instant;
+
+/// The W3C page load strategy (what chromedriver waits for on navigation)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PageLoadStrategy {
+    /// Waits for the 'load' event
+    #[default]
+    Normal,
+    /// Waits for the 'DOMContentLoaded' event
+    Eager,
+    /// Returns right after the navigation is started
+    None,
+}
+
+impl PageLoadStrategy {
+    /// Returns the capability value
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Normal => "normal",
+            Self::Eager => "eager",
+            Self::None => "none",
+        }
+    }
+}
+
+/// The condition of page readiness
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WaitUntil {
+    /// The page and all its resources are loaded
+    Load,
+    /// The HTML document is parsed
+    DomContentLoaded,
+    /// The document is parsed and at most 2 requests (e.g. long-polling) were in flight for the duration
+    NetworkIdle(Duration),
+    /// The element matching the CSS selector is present
+    Selector(String),
+}
+
+impl WaitUntil {
+    /// Returns the script checking the condition
+    pub(crate) fn script(&self) -> String {
+        match self {
+            Self::Load => str!("return document.readyState === 'complete';"),
+            Self::DomContentLoaded => str!("return document.readyState !== 'loading';"),
+            // the fallback without DevTools, the resources are counted by the observer installed once
+            // (the page resource timing buffer stops at 250 entries and is not resized):
+            Self::NetworkIdle(_) => str!("const key = Symbol.for('chromedriver-api.resources'); if (window[key] === undefined) {{ window[key] = performance.getEntriesByType('resource').length; new PerformanceObserver((list) => window[key] += list.getEntries().length).observe({{ type: 'resource' }}); }} return document.readyState === 'loading' ? -1 : window[key];"),
+            Self::Selector(selector) => fmt!(
+                "return document.querySelector({}) !== null;",
+                serde_json::to_string(selector).unwrap_or_default()
+            ),
+        }
+    }
+}
+
+/// The number of requests allowed in flight for the network idle (the long-polling connections)
+const IDLE_IN_FLIGHT: usize = 2;
+
+/// The tab navigation & network activity tracked by the CDP events
+pub(crate) struct PageWatcher {
+    events: EventStream<RawEvent>,
+    /// The main frame id (the tab target id)
+    frame_id: String,
+    /// The requests in flight (request ids)
+    in_flight: HashSet<String>,
+    /// The time of the last network activity
+    changed: Instant,
+    /// The main frame is navigated or the navigation is ended without a new document (always true without the navigation)
+    navigated: bool,
+    /// Some events are lost (the state is unknown)
+    lagged: bool,
+}
+
+impl PageWatcher {
+    /// Starts watching the tab
+    /// * frame_id: the main frame id (the tab target id)
+    /// * navigation: the navigation is about to start (the previous document must not be checked)
+    pub(crate) async fn start(cdp: &CdpSession, frame_id: &str, navigation: bool) -> Result<Self> {
+        let events = cdp.subscribe(&["Page", "Network"]).await?;
+
+        Ok(Self {
+            events,
+            frame_id: frame_id.to_owned(),
+            in_flight: HashSet::new(),
+            changed: Instant::now(),
+            navigated: !navigation,
+            lagged: false,
+        })
+    }
+
+    /// Handles the events received so far (without waiting)
+    pub(crate) fn poll(&mut self) {
+        while let Some(Some(event)) = self.events.next().now_or_never() {
+            // the lost events can't be restored, so the state is reset:
+            if event.lagged().is_some() {
+                self.in_flight.clear();
+                self.navigated = true;
+                self.lagged = true;
+                continue;
+            }
+
+            let request_id = || event.params["requestId"].as_str().unwrap_or_default().to_owned();
+
+            match event.method.as_str() {
+                // note: the server-sent events never end, so they are not counted:
+                "Network.requestWillBeSent" if event.params["type"] != "EventSource" => {
+                    self.in_flight.insert(request_id());
+                    self.changed = Instant::now();
+                },
+                "Network.loadingFinished" | "Network.loadingFailed" => {
+                    self.in_flight.remove(&request_id());
+                    self.changed = Instant::now();
+                },
+                "Page.frameNavigated" if event.params["frame"].get("parentId").is_none() => self.navigated = true,
+                "Page.navigatedWithinDocument" => self.navigated = true,
+                // the navigation is ended without a new document (e.g. a download or '204 No Content'):
+                "Page.frameStoppedLoading" if event.params["frameId"] == self.frame_id.as_str() => self.navigated = true,
+                "Page.downloadWillBegin" if event.params["frameId"] == self.frame_id.as_str() => self.navigated = true,
+                _ => {}
+            }
+        }
+    }
+
+    /// Returns true if the new document is navigated (or the navigation is ended without it)
+    pub(crate) fn navigated(&self) -> bool {
+        self.navigated
+    }
+
+    /// Returns true if some events are lost (the watcher state is unreliable)
+    pub(crate) fn lagged(&self) -> bool {
+        self.lagged
+    }
+
+    /// Returns how long the network is idle (None = too many requests are in flight)
+    pub(crate) fn idle_for(&self) -> Option<Duration> {
+        (self.in_flight.len() <= IDLE_IN_FLIGHT).then(|| self.changed.elapsed())
+    }
+}
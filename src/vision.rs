@@ -0,0 +1,322 @@
+use crate::prelude::*;
+
+use std::io::Cursor;
+use png::{ ColorType, Decoder, Transformations };
+
+/// The rectangle in viewport coordinates (CSS pixels)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Rect {
+    /// Returns the rectangle center point (useful for clicking)
+    pub fn center(&self) -> (i64, i64) {
+        ((self.x + self.width / 2.0).round() as i64, (self.y + self.height / 2.0).round() as i64)
+    }
+
+    /// Scales the rectangle by the factor
+    pub fn scale(&self, factor: f64) -> Self {
+        Self {
+            x: self.x * factor,
+            y: self.y * factor,
+            width: self.width * factor,
+            height: self.height * factor,
+        }
+    }
+}
+
+/// The template match found on a screenshot
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ImageMatch {
+    /// The matched area
+    pub rect: Rect,
+    /// The normalized cross-correlation score (-1.0 - 1.0, the higher is better)
+    pub score: f64,
+}
+
+/// The grayscale image used for template matching
+#[derive(Debug, Clone)]
+pub struct GrayImage {
+    width: usize,
+    height: usize,
+    pixels: Vec<f32>,
+}
+
+impl GrayImage {
+    /// Decodes the PNG image to grayscale
+    pub fn from_png(bytes: &[u8]) -> Result<Self> {
+        let mut decoder = Decoder::new(Cursor::new(bytes));
+        decoder.set_transformations(Transformations::normalize_to_color8());
+
+        let mut reader = decoder.read_info()?;
+        let mut buffer = vec![0; reader.output_buffer_size().ok_or(Error::InvalidImage)?];
+        let info = reader.next_frame(&mut buffer)?;
+
+        let channels = match info.color_type {
+            ColorType::Grayscale => 1,
+            ColorType::GrayscaleAlpha => 2,
+            ColorType::Rgb => 3,
+            ColorType::Rgba => 4,
+            ColorType::Indexed => return Err(Error::InvalidImage.into()),
+        };
+
+        let (width, height) = (info.width as usize, info.height as usize);
+        let mut pixels = Vec::with_capacity(width * height);
+        for row in buffer[..info.buffer_size()].chunks(info.line_size) {
+            for px in row[..width * channels].chunks(channels) {
+                let luma = match channels {
+                    1 | 2 => px[0] as f32,
+                    _ => 0.299 * px[0] as f32 + 0.587 * px[1] as f32 + 0.114 * px[2] as f32,
+                };
+                pixels.push(luma);
+            }
+        }
+
+        Ok(Self { width, height, pixels })
+    }
+
+    /// Returns the image width
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the image height
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the pixel brightness
+    fn at(&self, x: usize, y: usize) -> f32 {
+        self.pixels[y * self.width + x]
+    }
+
+    /// Reduces the image size by the factor (box filter)
+    fn downscale(&self, factor: usize) -> Self {
+        if factor <= 1 { return self.clone(); }
+
+        let (width, height) = (self.width / factor, self.height / factor);
+        let area = (factor * factor) as f32;
+        let mut pixels = Vec::with_capacity(width * height);
+
+        for y in 0..height {
+            for x in 0..width {
+                let mut sum = 0.0;
+                for dy in 0..factor {
+                    for dx in 0..factor {
+                        sum += self.at(x * factor + dx, y * factor + dy);
+                    }
+                }
+                pixels.push(sum / area);
+            }
+        }
+
+        Self { width, height, pixels }
+    }
+}
+
+/// The template prepared for normalized cross-correlation
+struct Template {
+    width: usize,
+    height: usize,
+    deviations: Vec<f32>,
+    norm: f64,
+}
+
+impl Template {
+    fn new(image: &GrayImage) -> Self {
+        let n = image.pixels.len() as f64;
+        let mean = image.pixels.iter().map(|&p| p as f64).sum::<f64>() / n;
+        let deviations = image.pixels.iter().map(|&p| (p as f64 - mean) as f32).collect::<Vec<_>>();
+        let norm = deviations.iter().map(|&d| (d as f64).powi(2)).sum::<f64>().sqrt();
+
+        Self { width: image.width, height: image.height, deviations, norm }
+    }
+}
+
+/// The summed-area tables of an image (for fast window mean & variance)
+struct Integral {
+    width: usize,
+    sum: Vec<f64>,
+    sq_sum: Vec<f64>,
+}
+
+impl Integral {
+    fn new(image: &GrayImage) -> Self {
+        let width = image.width + 1;
+        let mut sum = vec![0.0; width * (image.height + 1)];
+        let mut sq_sum = sum.clone();
+
+        for y in 0..image.height {
+            let (mut row, mut sq_row) = (0.0, 0.0);
+            for x in 0..image.width {
+                let p = image.at(x, y) as f64;
+                row += p;
+                sq_row += p * p;
+
+                let i = (y + 1) * width + x + 1;
+                sum[i] = sum[i - width] + row;
+                sq_sum[i] = sq_sum[i - width] + sq_row;
+            }
+        }
+
+        Self { width, sum, sq_sum }
+    }
+
+    /// Returns the sum and the squares sum of the window
+    fn window(&self, x: usize, y: usize, w: usize, h: usize) -> (f64, f64) {
+        let at = |table: &Vec<f64>| {
+            table[(y + h) * self.width + x + w] - table[y * self.width + x + w]
+                - table[(y + h) * self.width + x] + table[y * self.width + x]
+        };
+
+        (at(&self.sum), at(&self.sq_sum))
+    }
+}
+
+/// Computes the normalized cross-correlation of the template at the position
+fn correlation(image: &GrayImage, integral: &Integral, template: &Template, x: usize, y: usize) -> f64 {
+    let (w, h) = (template.width, template.height);
+    let n = (w * h) as f64;
+
+    let (sum, sq_sum) = integral.window(x, y, w, h);
+    let variance = sq_sum - sum * sum / n;
+    if variance <= 1e-6 || template.norm <= 1e-6 {
+        return 0.0;
+    }
+
+    let mut dot = 0.0f64;
+    for ty in 0..h {
+        let row = &image.pixels[(y + ty) * image.width + x..][..w];
+        let tpl = &template.deviations[ty * w..][..w];
+        dot += row.iter().zip(tpl).map(|(&p, &t)| p * t).sum::<f32>() as f64;
+    }
+
+    dot / (variance.sqrt() * template.norm)
+}
+
+/// Scans the area and returns the best match position & score
+fn scan(image: &GrayImage, template: &Template, xs: std::ops::RangeInclusive<usize>, ys: std::ops::RangeInclusive<usize>) -> Option<(usize, usize, f64)> {
+    let integral = Integral::new(image);
+    let mut best: Option<(usize, usize, f64)> = None;
+
+    for y in ys {
+        for x in xs.clone() {
+            let score = correlation(image, &integral, template, x, y);
+            if best.is_none_or(|(_, _, s)| score > s) {
+                best = Some((x, y, score));
+            }
+        }
+    }
+
+    best
+}
+
+/// Finds the best template match on the image (coarse-to-fine search),
+/// note: both images must have the same scale (e.g. the screenshot device pixels), the template is not resized
+pub fn find_template(image: &GrayImage, template: &GrayImage) -> Option<ImageMatch> {
+    if template.width == 0 || template.height == 0 || template.width > image.width || template.height > image.height {
+        return None;
+    }
+
+    // search on a reduced copy first (the template side should stay >= 12px):
+    let min_side = template.width.min(template.height);
+    let mut factor = 1;
+    while factor < 8 && min_side / (factor * 2) >= 12 {
+        factor *= 2;
+    }
+
+    let (mut bx, mut by) = (0, 0);
+    let (mut xs, mut ys) = (0..=image.width - template.width, 0..=image.height - template.height);
+
+    if factor > 1 {
+        let small_image = image.downscale(factor);
+        let small_template = Template::new(&template.downscale(factor));
+        let (sx, sy, _) = scan(
+            &small_image,
+            &small_template,
+            0..=small_image.width - small_template.width,
+            0..=small_image.height - small_template.height,
+        )?;
+
+        // refine around the coarse position:
+        (bx, by) = (sx * factor, sy * factor);
+        xs = bx.saturating_sub(factor * 2)..=(bx + factor * 2).min(image.width - template.width);
+        ys = by.saturating_sub(factor * 2)..=(by + factor * 2).min(image.height - template.height);
+    }
+
+    let template_data = Template::new(template);
+    let (x, y, score) = scan(image, &template_data, xs, ys).unwrap_or((bx, by, 0.0));
+
+    Some(ImageMatch {
+        rect: Rect {
+            x: x as f64,
+            y: y as f64,
+            width: template.width as f64,
+            height: template.height as f64,
+        },
+        score,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates the noise image (deterministic, no repeated areas)
+    fn noise(width: usize, height: usize, seed: u64) -> GrayImage {
+        let mut state = seed;
+        let pixels = (0..width * height)
+            .map(|_| {
+                state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                (state >> 56) as f32
+            })
+            .collect();
+
+        GrayImage { width, height, pixels }
+    }
+
+    /// Cuts the image area
+    fn crop(image: &GrayImage, x: usize, y: usize, width: usize, height: usize) -> GrayImage {
+        let pixels = (y..y + height)
+            .flat_map(|row| (x..x + width).map(move |col| (row, col)))
+            .map(|(row, col)| image.at(col, row))
+            .collect();
+
+        GrayImage { width, height, pixels }
+    }
+
+    #[test]
+    fn finds_exact_template_position() {
+        let image = noise(200, 120, 7);
+
+        // the small template is searched directly, the larger ones are searched on the reduced copies first:
+        for (x, y, width, height) in [(13, 9, 16, 16), (37, 21, 40, 30), (101, 47, 64, 56)] {
+            let found = find_template(&image, &crop(&image, x, y, width, height)).unwrap();
+
+            assert_eq!(found.rect, Rect { x: x as f64, y: y as f64, width: width as f64, height: height as f64 });
+            assert!(found.score > 0.999, "score {}", found.score);
+        }
+    }
+
+    #[test]
+    fn skips_template_larger_than_image() {
+        let image = noise(50, 40, 1);
+
+        assert!(find_template(&image, &noise(60, 20, 2)).is_none());
+        assert!(find_template(&image, &noise(20, 41, 3)).is_none());
+        assert!(find_template(&image, &noise(0, 0, 4)).is_none());
+    }
+
+    #[test]
+    fn downscales_by_box_filter() {
+        let image = GrayImage { width: 5, height: 4, pixels: (0..20).map(|p| p as f32).collect() };
+        let small = image.downscale(2);
+
+        assert_eq!((small.width(), small.height()), (2, 2));
+        assert_eq!(small.pixels, vec![3.0, 5.0, 13.0, 15.0]);
+    }
+}
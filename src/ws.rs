@@ -0,0 +1,167 @@
+use crate::prelude::*;
+
+use std::collections::HashMap;
+use std::sync::atomic::{ AtomicU64, Ordering };
+use futures::{ SinkExt, StreamExt };
+use serde_json::Value;
+use tokio::sync::{ broadcast, mpsc, oneshot };
+use tokio_tungstenite::{ connect_async, tungstenite::Message };
+
+/// The capacity of the incoming events queue (slow subscribers lose the oldest events)
+pub(crate) const EVENTS_CAPACITY: usize = 4096;
+
+/// The command response timeout
+const CALL_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The waiting command responses by message id (None = the connection is closed)
+type Pending = Arc<std::sync::Mutex<Option<HashMap<u64, oneshot::Sender<Value>>>>>;
+
+/// The events sender (None = the connection is closed, so the subscribers streams are ended)
+type Events = Arc<std::sync::Mutex<Option<broadcast::Sender<Value>>>>;
+
+/// The JSON-RPC-like WebSocket client (used by CDP and WebDriver BiDi)
+pub(crate) struct WsClient {
+    sender: mpsc::UnboundedSender<Message>,
+    pending: Pending,
+    events: Events,
+    next_id: AtomicU64,
+}
+
+impl WsClient {
+    /// Connects to the WebSocket URL and starts the reading & writing tasks
+    pub(crate) async fn connect(url: &str) -> Result<Self> {
+        let (stream, _) = connect_async(url)
+            .await
+            .map_err(|e| Error::WebSocketFailed(e.to_string()))?;
+        let (mut write, mut read) = stream.split();
+
+        let (sender, mut outgoing) = mpsc::unbounded_channel::<Message>();
+        let pending: Pending = Arc::new(std::sync::Mutex::new(Some(HashMap::new())));
+        let (events_sender, _) = broadcast::channel(EVENTS_CAPACITY);
+        let events: Events = Arc::new(std::sync::Mutex::new(Some(events_sender.clone())));
+
+        // writing messages:
+        tokio::spawn(async move {
+            while let Some(message) = outgoing.recv().await {
+                if write.send(message).await.is_err() { break; }
+            }
+            let _ = write.close().await;
+        });
+
+        // reading responses & events:
+        {
+            let pending = pending.clone();
+            let events = events.clone();
+            let sender = events_sender;
+
+            tokio::spawn(async move {
+                while let Some(Ok(message)) = read.next().await {
+                    let Message::Text(text) = message else { continue };
+                    let Ok(value) = serde_json::from_str::<Value>(text.as_str()) else { continue };
+
+                    match value.get("id").and_then(|id| id.as_u64()) {
+                        Some(id) => {
+                            let waiter = pending.lock().unwrap().as_mut().and_then(|pending| pending.remove(&id));
+                            if let Some(waiter) = waiter {
+                                let _ = waiter.send(value);
+                            }
+                        },
+                        None => { let _ = sender.send(value); }
+                    }
+                }
+
+                // connection closed, fail the waiting & the next commands and end the events streams:
+                pending.lock().unwrap().take();
+                events.lock().unwrap().take();
+            });
+        }
+
+        Ok(Self {
+            sender,
+            pending,
+            events,
+            next_id: AtomicU64::new(1),
+        })
+    }
+
+    /// Sends the message (the 'id' field is set automatically) and waits for the response with the same id
+    pub(crate) async fn call(&self, mut message: Value) -> Result<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        message["id"] = id.into();
+
+        let (waiter, response) = oneshot::channel();
+        match self.pending.lock().unwrap().as_mut() {
+            Some(pending) => { pending.insert(id, waiter); },
+            None => return Err(Error::WebSocketFailed(str!("the connection is closed")).into()),
+        }
+
+        if self.sender.send(Message::text(message.to_string())).is_err() {
+            self.forget(id);
+            return Err(Error::WebSocketFailed(str!("the connection is closed")).into());
+        }
+
+        match tokio::time::timeout(CALL_TIMEOUT, response).await {
+            Ok(Ok(response)) => Ok(response),
+            Ok(Err(_)) => Err(Error::WebSocketFailed(str!("the connection is closed")).into()),
+            Err(_) => {
+                self.forget(id);
+                Err(Error::WebSocketFailed(fmt!("no response in {} seconds", CALL_TIMEOUT.as_secs())).into())
+            }
+        }
+    }
+
+    /// Drops the waiting command response
+    fn forget(&self, id: u64) {
+        if let Some(pending) = self.pending.lock().unwrap().as_mut() {
+            pending.remove(&id);
+        }
+    }
+
+    /// Returns true if the connection is closed (the commands fail and the events streams are ended)
+    pub(crate) fn is_closed(&self) -> bool {
+        self.pending.lock().unwrap().is_none()
+    }
+
+    /// Subscribes to the incoming messages without id (events), the receiver is closed with the connection
+    pub(crate) fn subscribe(&self) -> broadcast::Receiver<Value> {
+        match self.events.lock().unwrap().as_ref() {
+            Some(events) => events.subscribe(),
+            None => broadcast::channel(1).1,
+        }
+    }
+}
+
+impl Drop for WsClient {
+    fn drop(&mut self) {
+        let _ = self.sender.send(Message::Close(None));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn ends_events_when_connection_is_closed() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = fmt!("ws://{}", listener.local_addr().unwrap());
+
+        // the server sends one event and disconnects:
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            ws.send(Message::text(r#"{"method":"Test.event"}"#)).await.unwrap();
+            ws.close(None).await.unwrap();
+        });
+
+        let client = WsClient::connect(&url).await.unwrap();
+        let mut events = client.subscribe();
+        server.await.unwrap();
+
+        assert_eq!(events.recv().await.unwrap()["method"], "Test.event");
+        assert!(matches!(events.recv().await, Err(broadcast::error::RecvError::Closed)));
+        assert!(client.is_closed());
+        assert!(matches!(client.subscribe().recv().await, Err(broadcast::error::RecvError::Closed)));
+        assert!(client.call(serde_json::json!({ "method": "Test.command" })).await.is_err());
+    }
+}